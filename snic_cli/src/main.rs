@@ -0,0 +1,274 @@
+//! Command-line front end of the snic engine, for use in shell pipelines.
+//! Matchups travel as lines of whitespace-separated item indices.
+mod results;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use snic_core::common_types::{BaseInt, InputInt};
+use snic_core::gber::Decomposition;
+use snic_core::error::SnicError;
+use snic_core::network::comparison::{self, MatchupResult};
+use snic_core::network::matchup::{LocalMatchupsManager, MatchupOrdering};
+use snic_core::network::statistics;
+use snic_core::network::rank::{
+    self, BradleyTerryRanker, InterimRanking, PageRankRanker, PlackettLuceRanker, Ranker,
+    RankingOutcome,
+};
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "snic", version, about = "Plan and rank SNIC tournaments")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print every matchup of a network, one per line.
+    Plan {
+        network_size: InputInt,
+        base: BaseInt,
+        #[arg(long, value_enum, default_value_t = OrderingArg::IntraFirst)]
+        ordering: OrderingArg,
+        /// Shuffle the items over the network positions, reproducibly.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Print only this shard of the plan, counted from 0.
+        #[arg(long, requires = "shard_count", default_value_t = 0)]
+        shard_index: usize,
+        /// Split the plan into this many disjoint, balanced shards.
+        #[arg(long)]
+        shard_count: Option<usize>,
+    },
+    /// Rank items from ranked matchups read on stdin, best item first.
+    /// Tied items are joined with `=`, and items after a `|` are unranked.
+    /// Prints the position, item and score of every item, strongest first.
+    Rank {
+        #[arg(long, value_enum, default_value_t = ModelArg::BradleyTerry)]
+        model: ModelArg,
+        /// List every item of a network of this size, even those not compared yet,
+        /// and report the share of its planned comparisons observed.
+        #[arg(long, requires = "base")]
+        network_size: Option<InputInt>,
+        /// Base of the planned network, with `--network-size`.
+        #[arg(long, requires = "network_size")]
+        base: Option<BaseInt>,
+    },
+    /// Print the decomposition of a network and the size of its plan.
+    Stats {
+        network_size: InputInt,
+        base: BaseInt,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OrderingArg {
+    IntraFirst,
+    InterFirst,
+    Interleaved,
+}
+
+impl From<OrderingArg> for MatchupOrdering {
+    fn from(ordering: OrderingArg) -> Self {
+        match ordering {
+            OrderingArg::IntraFirst => Self::IntraFirst,
+            OrderingArg::InterFirst => Self::InterFirst,
+            OrderingArg::Interleaved => Self::Interleaved,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ModelArg {
+    PageRank,
+    BradleyTerry,
+    PlackettLuce,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let outcome = match cli.command {
+        Command::Plan {
+            network_size,
+            base,
+            ordering,
+            seed,
+            shard_index,
+            shard_count,
+        } => plan(network_size, base, ordering.into(), seed, shard_index, shard_count),
+        Command::Rank {
+            model,
+            network_size,
+            base,
+        } => rank(model, network_size.zip(base)),
+        Command::Stats { network_size, base } => stats(network_size, base),
+    };
+    match outcome {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("snic: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+fn plan(
+    network_size: InputInt,
+    base: BaseInt,
+    ordering: MatchupOrdering,
+    seed: Option<u64>,
+    shard_index: usize,
+    shard_count: Option<usize>,
+) -> CliResult {
+    let decomposition = Decomposition::new(network_size, base)?;
+    let mut manager = LocalMatchupsManager::new(decomposition)?.with_ordering(ordering);
+    if let Some(seed) = seed {
+        manager = manager.with_seed(seed);
+    }
+    let matchups: Box<dyn Iterator<Item = Vec<InputInt>>> = match shard_count {
+        Some(shard_count) => Box::new(manager.shard(shard_index, shard_count)?),
+        None => Box::new(manager),
+    };
+    let mut output = BufWriter::new(io::stdout().lock());
+    for matchup in matchups {
+        writeln!(output, "{}", results::format_items(&matchup))?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+fn rank(model: ModelArg, network: Option<(InputInt, BaseInt)>) -> CliResult {
+    let matchup_results = results::read_matchup_results(io::stdin().lock())?;
+    match network {
+        Some((network_size, base)) => {
+            let ranking = interim_ranking_of(model, network_size, base, &matchup_results)?;
+            report_convergence(&ranking.outcome);
+            eprintln!(
+                "snic: observed {} of {} planned comparisons ({:.1}%), {} items never compared",
+                ranking.observed_comparisons,
+                ranking.planned_comparisons,
+                ranking.coverage() * 100.0,
+                ranking.never_compared().len()
+            );
+            write_leaderboard(&ranking.outcome)
+        }
+        None => {
+            let outcome = outcome_of(model, &matchup_results)?;
+            report_convergence(&outcome);
+            write_leaderboard(&outcome)
+        }
+    }
+}
+
+fn outcome_of(model: ModelArg, matchup_results: &[MatchupResult]) -> Result<RankingOutcome, SnicError> {
+    Ok(match model {
+        ModelArg::PageRank => {
+            PageRankRanker::default().rank(&comparison::expand_matchup_results(matchup_results)?)
+        }
+        ModelArg::BradleyTerry => BradleyTerryRanker::default()
+            .rank(&comparison::expand_matchup_results(matchup_results)?),
+        ModelArg::PlackettLuce => {
+            PlackettLuceRanker::default()
+                .rank_matchups(matchup_results)?
+                .ranking
+        }
+    })
+}
+
+/// Before the first results come in, every item of the network is still to be compared.
+fn interim_ranking_of(
+    model: ModelArg,
+    network_size: InputInt,
+    base: BaseInt,
+    matchup_results: &[MatchupResult],
+) -> Result<InterimRanking, SnicError> {
+    let outcome = if matchup_results.is_empty() {
+        RankingOutcome::empty()
+    } else {
+        outcome_of(model, matchup_results)?
+    };
+    InterimRanking::new(network_size, base, matchup_results, outcome)
+}
+
+fn report_convergence(outcome: &RankingOutcome) {
+    if !outcome.converged {
+        eprintln!(
+            "snic: the model did not converge after {} iterations",
+            outcome.iterations
+        );
+    }
+}
+
+fn write_leaderboard(outcome: &RankingOutcome) -> CliResult {
+    let mut output = BufWriter::new(io::stdout().lock());
+    for (position, item) in rank::order_by_scores(&outcome.scores, true).into_iter().enumerate() {
+        writeln!(output, "{position}\t{item}\t{}", outcome.scores[item as usize])?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+fn stats(network_size: InputInt, base: BaseInt) -> CliResult {
+    let statistics = statistics::calculate_plan_statistics_for(network_size, base)?;
+    let mut output = BufWriter::new(io::stdout().lock());
+    writeln!(output, "network size\t{network_size}")?;
+    writeln!(output, "base\t{base}")?;
+    writeln!(
+        output,
+        "component powers\t{:?}",
+        statistics.decomposition.component_powers
+    )?;
+    writeln!(output, "remainder\t{}", statistics.decomposition.remainder)?;
+    for (position, (subnetwork_size, matchups)) in statistics
+        .subnetwork_sizes
+        .iter()
+        .zip(&statistics.subnetwork_matchups)
+        .enumerate()
+    {
+        writeln!(
+            output,
+            "subnetwork {position}\t{subnetwork_size} items\t{matchups} matchups"
+        )?;
+    }
+    writeln!(
+        output,
+        "inter-subnetwork matchups\t{}",
+        statistics.intersubnetwork_matchups
+    )?;
+    writeln!(output, "total matchups\t{}", statistics.total_matchups)?;
+    writeln!(output, "total comparisons\t{}", statistics.total_comparisons)?;
+    for (matchups, items) in &statistics.participation_histogram {
+        writeln!(output, "items in {matchups} matchups\t{items}")?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod test_rank {
+    use super::{interim_ranking_of, outcome_of, ModelArg};
+    use snic_core::error::SnicError;
+
+    #[test]
+    fn list_network_before_any_result() {
+        for model in [ModelArg::PageRank, ModelArg::BradleyTerry, ModelArg::PlackettLuce] {
+            let ranking = interim_ranking_of(model, 9, 3, &[]).unwrap();
+            assert_eq!(ranking.standings.len(), 9);
+            assert_eq!(ranking.observed_comparisons, 0);
+            assert_eq!(ranking.never_compared(), (0..9).collect::<Vec<_>>());
+            assert!(ranking.outcome.scores.iter().all(|score| score.is_nan()));
+        }
+    }
+
+    #[test]
+    fn reject_empty_results_without_network() {
+        assert!(matches!(
+            outcome_of(ModelArg::BradleyTerry, &[]),
+            Err(SnicError::InvalidRankings(_))
+        ));
+    }
+}
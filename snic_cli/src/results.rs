@@ -0,0 +1,87 @@
+//! Plain-text format of ranked matchups.
+//! One matchup per line, best item first: `3 1=4 | 0 2`
+//! ranks 3 above the tied 1 and 4, with 0 and 2 left unranked below them.
+//! Blank lines and lines starting with `#` are skipped.
+use snic_core::common_types::InputInt;
+use snic_core::error::SnicError;
+use snic_core::network::comparison::MatchupResult;
+use std::io::BufRead;
+
+pub fn read_matchup_results(
+    input: impl BufRead,
+) -> Result<Vec<MatchupResult>, Box<dyn std::error::Error>> {
+    let mut matchup_results = vec![];
+    for (line_number, line) in input.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        matchup_results.push(parse_matchup_result(line).map_err(|reason| {
+            SnicError::InvalidRankings(format!("line {}: {reason}", line_number + 1))
+        })?);
+    }
+    Ok(matchup_results)
+}
+
+pub fn parse_matchup_result(line: &str) -> Result<MatchupResult, String> {
+    let (ranked, unranked) = line.split_once('|').unwrap_or((line, ""));
+    let tiers = ranked
+        .split_whitespace()
+        .map(|tier| tier.split('=').map(parse_item).collect())
+        .collect::<Result<Vec<Vec<InputInt>>, String>>()?;
+    let unranked = unranked
+        .split_whitespace()
+        .map(parse_item)
+        .collect::<Result<Vec<InputInt>, String>>()?;
+    Ok(MatchupResult {
+        tiers,
+        unranked,
+        ..Default::default()
+    })
+}
+
+fn parse_item(token: &str) -> Result<InputInt, String> {
+    token
+        .parse()
+        .map_err(|_| format!("`{token}` is not an item index"))
+}
+
+pub fn format_items(items: &[InputInt]) -> String {
+    items
+        .iter()
+        .map(InputInt::to_string)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test_matchup_result_format {
+    use super::{parse_matchup_result, read_matchup_results};
+
+    #[test]
+    fn parse_strict_ordering() {
+        let result = parse_matchup_result("2 0 1").unwrap();
+        assert_eq!(result.tiers, vec![vec![2], vec![0], vec![1]]);
+        assert!(result.unranked.is_empty());
+    }
+
+    #[test]
+    fn parse_ties_and_unranked() {
+        let result = parse_matchup_result("3 1=4 | 0 2").unwrap();
+        assert_eq!(result.tiers, vec![vec![3], vec![1, 4]]);
+        assert_eq!(result.unranked, vec![0, 2]);
+    }
+
+    #[test]
+    fn skip_comments_and_blanks() {
+        let input = "# judge A\n0 1\n\n1 2\n";
+        assert_eq!(read_matchup_results(input.as_bytes()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn report_line_of_bad_items() {
+        let error = read_matchup_results("0 1\n0 x\n".as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+    }
+}
@@ -0,0 +1,200 @@
+//! Mapping between caller-side item labels and network indices.
+//! Labels are numbered in the order they are given,
+//! so the same list of labels always yields the same network.
+use crate::common_types::{BaseInt, InputInt};
+use crate::error::SnicError;
+use crate::gber::Decomposition;
+use crate::network::comparison::MatchupResult;
+use crate::network::matchup::{LocalMatchupsManager, MatchupOrdering};
+use crate::network::rank::{self, Ranker, Standing};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+#[derive(Debug, Clone)]
+pub struct Catalog<L> {
+    labels: Vec<L>,
+    indices: HashMap<L, InputInt>,
+}
+
+impl<L: Clone + Debug + Eq + Hash> Catalog<L> {
+    pub fn new(labels: impl IntoIterator<Item = L>) -> Result<Self, SnicError> {
+        let labels = labels.into_iter().collect::<Vec<L>>();
+        InputInt::try_from(labels.len()).map_err(|_| SnicError::Overflow)?;
+        let mut indices = HashMap::with_capacity(labels.len());
+        for (index, label) in labels.iter().enumerate() {
+            if indices.insert(label.clone(), index as InputInt).is_some() {
+                return Err(SnicError::InvalidLabel(format!(
+                    "{label:?} is listed more than once"
+                )));
+            }
+        }
+        Ok(Self { labels, indices })
+    }
+
+    pub fn len(&self) -> InputInt {
+        self.labels.len() as InputInt
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Labels in index order.
+    pub fn labels(&self) -> &[L] {
+        &self.labels
+    }
+
+    pub fn index_of(&self, label: &L) -> Result<InputInt, SnicError> {
+        self.indices
+            .get(label)
+            .copied()
+            .ok_or_else(|| SnicError::InvalidLabel(format!("{label:?} is not in the catalog")))
+    }
+
+    pub fn label_of(&self, index: InputInt) -> Option<&L> {
+        self.labels.get(index as usize)
+    }
+
+    /// Stream the matchups of the whole catalog, as labels.
+    pub fn matchups(
+        &self,
+        base: BaseInt,
+        ordering: MatchupOrdering,
+    ) -> Result<impl Iterator<Item = Vec<&L>> + '_, SnicError> {
+        let manager =
+            LocalMatchupsManager::new(Decomposition::new(self.len(), base)?)?.with_ordering(ordering);
+        Ok(manager.map(|matchup| {
+            matchup
+                .into_iter()
+                .map(|index| &self.labels[index as usize])
+                .collect()
+        }))
+    }
+
+    /// Translate a labelled result, with tiers from best to worst.
+    pub fn index_result(&self, tiers: &[Vec<L>], unranked: &[L]) -> Result<MatchupResult, SnicError> {
+        Ok(MatchupResult {
+            tiers: tiers
+                .iter()
+                .map(|tier| tier.iter().map(|label| self.index_of(label)).collect())
+                .collect::<Result<_, _>>()?,
+            unranked: unranked
+                .iter()
+                .map(|label| self.index_of(label))
+                .collect::<Result<_, _>>()?,
+            ..Default::default()
+        })
+    }
+
+    /// Every label with its standing, strongest first.
+    /// Labels never compared in the results come last, with a NaN score.
+    pub fn leaderboard(
+        &self,
+        ranker: &impl Ranker,
+        base: BaseInt,
+        results: &[MatchupResult],
+    ) -> Result<Vec<(&L, Standing)>, SnicError> {
+        let ranking = rank::rank_interim(self.len(), base, results, ranker)?;
+        Ok(ranking
+            .standings
+            .into_iter()
+            .map(|standing| (&self.labels[standing.item as usize], standing))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test_catalog {
+    use super::Catalog;
+    use crate::error::SnicError;
+    use crate::network::matchup::MatchupOrdering;
+    use crate::network::rank::BradleyTerryRanker;
+
+    fn fruits() -> Catalog<&'static str> {
+        Catalog::new(["apple", "banana", "cherry", "date", "elder", "fig"]).unwrap()
+    }
+
+    #[test]
+    fn reject_duplicate_labels() {
+        assert!(matches!(
+            Catalog::new(["apple", "banana", "apple"]),
+            Err(SnicError::InvalidLabel(_))
+        ));
+    }
+
+    #[test]
+    fn reject_unknown_labels() {
+        assert!(matches!(
+            fruits().index_result(&[vec!["apple"], vec!["grape"]], &[]),
+            Err(SnicError::InvalidLabel(_))
+        ));
+    }
+
+    #[test]
+    fn labels_round_trip() {
+        let catalog = fruits();
+        for (index, label) in catalog.labels().iter().enumerate() {
+            assert_eq!(catalog.index_of(label).unwrap() as usize, index);
+            assert_eq!(catalog.label_of(index as _), Some(label));
+        }
+        assert_eq!(catalog.label_of(catalog.len()), None);
+    }
+
+    #[test]
+    fn labelled_matchups_cover_catalog() {
+        let catalog = fruits();
+        let mut seen = catalog
+            .matchups(2, MatchupOrdering::default())
+            .unwrap()
+            .flatten()
+            .copied()
+            .collect::<Vec<&str>>();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), catalog.labels().len());
+    }
+
+    #[test]
+    fn leaderboard_follows_results() {
+        let catalog = fruits();
+        // Alphabetical order is reversed: the later the letter, the stronger the fruit.
+        let results = catalog
+            .matchups(3, MatchupOrdering::default())
+            .unwrap()
+            .map(|mut matchup| {
+                matchup.sort_by(|left, right| right.cmp(left));
+                let tiers = matchup.into_iter().map(|label| vec![*label]).collect::<Vec<_>>();
+                catalog.index_result(&tiers, &[]).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let leaderboard = catalog
+            .leaderboard(&BradleyTerryRanker::default(), 3, &results)
+            .unwrap();
+        assert_eq!(leaderboard.len(), 6);
+        assert_eq!(leaderboard.first().map(|entry| *entry.0), Some("fig"));
+        assert_eq!(leaderboard.last().map(|entry| *entry.0), Some("apple"));
+    }
+
+    #[test]
+    fn leaderboard_lists_labels_never_compared_last() {
+        let catalog = fruits();
+        // "fig" never plays, though it is the last label
+        let results = [
+            catalog
+                .index_result(&[vec!["cherry"], vec!["banana"], vec!["apple"]], &[])
+                .unwrap(),
+            catalog
+                .index_result(&[vec!["elder"], vec!["date"], vec!["cherry"]], &[])
+                .unwrap(),
+        ];
+        let leaderboard = catalog
+            .leaderboard(&BradleyTerryRanker::default(), 3, &results)
+            .unwrap();
+        assert_eq!(leaderboard.len(), 6);
+        assert_eq!(leaderboard[0].0, &"elder");
+        let (label, standing) = leaderboard.last().unwrap();
+        assert_eq!(*label, &"fig");
+        assert!(standing.never_compared() && standing.score.is_nan());
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(not(feature = "u64-indices"))]
 pub type InputInt = u32;
+#[cfg(feature = "u64-indices")]
+pub type InputInt = u64;
 pub type BaseInt = u16;
-pub const INPUT_BIT_WIDTH: usize = 32;
+pub const INPUT_BIT_WIDTH: usize = InputInt::BITS as usize;
@@ -2,7 +2,242 @@ use crate::common_types::{BaseInt, InputInt};
 
 /// Calculate the integer logarithm of a number.
 /// This had different implementations historically.
+/// The floating point one drifted near exact powers,
+/// so this stays within integer arithmetic.
+/// Numbers below the base, including 0, have a logarithm of 0.
 pub fn integer_log(number: InputInt, base: BaseInt) -> u8 {
     // The floor of the log base of a number
-    (number as f64).log(base as f64).floor() as u8
+    number.checked_ilog(base as InputInt).unwrap_or(0) as u8
+}
+
+/// Small seeded generator for reproducible resampling.
+/// SplitMix64: not cryptographic, but every seed replays the same stream.
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut mixed = self.state;
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^ (mixed >> 31)
+    }
+
+    /// Draw from 0 up to the exclusive bound.
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+/// Seeded bijection over the items `0..size`, computed on the fly.
+/// A balanced Feistel network over the next even power of two,
+/// cycle-walked until it lands back within the size.
+/// A lookup averages under four passes, and nothing is stored per item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeededPermutation {
+    size: InputInt,
+    seed: u64,
+    half_bits: u32,
+    round_keys: [u64; Self::ROUNDS],
+}
+
+// The casts to u64 are no-ops under the u64-indices feature.
+#[allow(clippy::unnecessary_cast)]
+impl SeededPermutation {
+    const ROUNDS: usize = 4;
+
+    pub fn new(size: InputInt, seed: u64) -> Self {
+        let domain_bits = (u64::BITS - (size as u64).saturating_sub(1).leading_zeros()).max(1);
+        let mut generator = SplitMix64::new(seed);
+        Self {
+            size,
+            seed,
+            half_bits: domain_bits.div_ceil(2),
+            round_keys: std::array::from_fn(|_| generator.next_u64()),
+        }
+    }
+
+    pub fn size(&self) -> InputInt {
+        self.size
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Network position assigned to an item below the size.
+    pub fn position_of(&self, item: InputInt) -> InputInt {
+        debug_assert!(item < self.size);
+        let mut position = item as u64;
+        loop {
+            position = self.encrypt(position);
+            if position < self.size as u64 {
+                return position as InputInt;
+            }
+        }
+    }
+
+    /// Item placed at a network position below the size.
+    pub fn item_at(&self, position: InputInt) -> InputInt {
+        debug_assert!(position < self.size);
+        let mut item = position as u64;
+        loop {
+            item = self.decrypt(item);
+            if item < self.size as u64 {
+                return item as InputInt;
+            }
+        }
+    }
+
+    fn half_mask(&self) -> u64 {
+        u64::MAX >> (u64::BITS - self.half_bits)
+    }
+
+    fn scramble(&self, half: u64, key: u64) -> u64 {
+        let mut mixed = (half ^ key).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed ^= mixed >> 31;
+        mixed = mixed.wrapping_mul(0x94D0_49BB_1331_11EB);
+        (mixed ^ (mixed >> 29)) & self.half_mask()
+    }
+
+    fn encrypt(&self, value: u64) -> u64 {
+        let (mut left, mut right) = (value >> self.half_bits, value & self.half_mask());
+        for &key in &self.round_keys {
+            (left, right) = (right, left ^ self.scramble(right, key));
+        }
+        (left << self.half_bits) | right
+    }
+
+    fn decrypt(&self, value: u64) -> u64 {
+        let (mut left, mut right) = (value >> self.half_bits, value & self.half_mask());
+        for &key in self.round_keys.iter().rev() {
+            (left, right) = (right ^ self.scramble(left, key), left);
+        }
+        (left << self.half_bits) | right
+    }
+}
+
+#[cfg(test)]
+mod test_seeded_permutation {
+    use super::SeededPermutation;
+    use crate::common_types::InputInt;
+
+    #[test]
+    fn bijective_for_small_sizes() {
+        for size in 1..300 {
+            for seed in [0, 1, 0xDEAD_BEEF] {
+                let permutation = SeededPermutation::new(size, seed);
+                let mut positions = (0..size)
+                    .map(|item| permutation.position_of(item))
+                    .collect::<Vec<InputInt>>();
+                for (item, &position) in positions.iter().enumerate() {
+                    assert_eq!(permutation.item_at(position) as usize, item);
+                }
+                positions.sort();
+                assert!(positions.into_iter().eq(0..size), "size {size}, seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_near_type_maximum() {
+        let permutation = SeededPermutation::new(InputInt::MAX, 42);
+        for item in (InputInt::MAX - 1000)..InputInt::MAX {
+            assert_eq!(permutation.item_at(permutation.position_of(item)), item);
+        }
+    }
+
+    #[test]
+    fn replay_from_seed() {
+        let positions = |seed| {
+            let permutation = SeededPermutation::new(1000, seed);
+            (0..1000).map(|item| permutation.position_of(item)).collect::<Vec<InputInt>>()
+        };
+        assert_eq!(positions(7), positions(7));
+        assert_ne!(positions(7), positions(8));
+        assert_ne!(positions(7), (0..1000).collect::<Vec<InputInt>>());
+    }
+}
+
+#[cfg(test)]
+mod test_split_mix {
+    use super::SplitMix64;
+
+    #[test]
+    fn replay_from_seed() {
+        let draws = |seed| {
+            let mut generator = SplitMix64::new(seed);
+            (0..100).map(|_| generator.next_u64()).collect::<Vec<u64>>()
+        };
+        assert_eq!(draws(7), draws(7));
+        assert_ne!(draws(7), draws(8));
+    }
+
+    #[test]
+    fn stay_below_bound() {
+        let mut generator = SplitMix64::new(0);
+        let mut seen = [false; 10];
+        for _ in 0..1000 {
+            let draw = generator.next_below(10);
+            assert!(draw < 10);
+            seen[draw as usize] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+    }
+}
+
+#[cfg(test)]
+mod test_integer_log {
+    use super::integer_log;
+    use crate::common_types::{BaseInt, InputInt};
+
+    #[test]
+    fn power_boundaries_for_all_bases() {
+        for base in 2..=BaseInt::MAX {
+            let mut power = base as InputInt;
+            let mut exponent = 1;
+            loop {
+                assert_eq!(integer_log(power, base), exponent, "{base}^{exponent}");
+                assert_eq!(integer_log(power - 1, base), exponent - 1, "{base}^{exponent} - 1");
+                if power < InputInt::MAX {
+                    assert_eq!(integer_log(power + 1, base), exponent, "{base}^{exponent} + 1");
+                }
+                match power.checked_mul(base as InputInt) {
+                    Some(next_power) => power = next_power,
+                    None => break,
+                }
+                exponent += 1;
+            }
+            assert_eq!(integer_log(InputInt::MAX, base), exponent, "{base} at the type maximum");
+        }
+    }
+
+    #[test]
+    fn bracketed_by_powers() {
+        let step = InputInt::MAX / 9973;
+        for base in [2, 3, 5, 7, 10, 16, 255, 1000, 4093, BaseInt::MAX] {
+            for number in (1..=InputInt::MAX).step_by(step as usize) {
+                let exponent = integer_log(number, base) as u32;
+                let lower = (base as InputInt).pow(exponent);
+                assert!(lower <= number);
+                if let Some(upper) = (base as InputInt).checked_pow(exponent + 1) {
+                    assert!(number < upper);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn below_base() {
+        for number in 0..10 {
+            assert_eq!(integer_log(number, 10), 0);
+        }
+    }
 }
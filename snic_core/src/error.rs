@@ -0,0 +1,49 @@
+//! Errors surfaced by the public constructors of the crate.
+use crate::common_types::{BaseInt, InputInt};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnicError {
+    /// The base must be greater than 1.
+    InvalidBase(BaseInt),
+    /// The network has no items to compare.
+    EmptyNetwork,
+    /// The network cannot fill a single matchup.
+    NetworkSmallerThanMatchup {
+        network_size: InputInt,
+        matchup_size: BaseInt,
+    },
+    /// An intermediate value does not fit into the index type.
+    Overflow,
+    /// The ranked matchups cannot be expanded into comparisons.
+    InvalidRankings(String),
+    /// A tuning parameter is out of its range.
+    InvalidParameter(String),
+    /// An item label is duplicated or missing from the catalog.
+    InvalidLabel(String),
+    /// A result does not answer a pending matchup.
+    InvalidResult(String),
+}
+
+impl fmt::Display for SnicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBase(base) => write!(f, "The base must be greater than 1, got {base}."),
+            Self::EmptyNetwork => write!(f, "The network must contain at least one item."),
+            Self::NetworkSmallerThanMatchup {
+                network_size,
+                matchup_size,
+            } => write!(
+                f,
+                "The network of {network_size} items is smaller than a matchup of {matchup_size}."
+            ),
+            Self::Overflow => write!(f, "The network is too large for the index type."),
+            Self::InvalidRankings(reason) => write!(f, "Invalid rankings: {reason}"),
+            Self::InvalidParameter(reason) => write!(f, "Invalid parameter: {reason}"),
+            Self::InvalidLabel(reason) => write!(f, "Invalid label: {reason}"),
+            Self::InvalidResult(reason) => write!(f, "Invalid result: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for SnicError {}
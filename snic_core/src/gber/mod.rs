@@ -10,6 +10,7 @@
 
 use crate::common_types::{BaseInt, InputInt};
 use crate::common_utilities;
+use crate::error::SnicError;
 
 /// GBER of a number.
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -23,14 +24,14 @@ pub struct Decomposition {
 }
 
 impl Decomposition {
-    pub fn new(decimal_number: InputInt, base: BaseInt) -> Result<Self, &'static str> {
+    pub fn new(decimal_number: InputInt, base: BaseInt) -> Result<Self, SnicError> {
         if base < 2 {
-            return Err("The base must be greater than 1.");
+            return Err(SnicError::InvalidBase(base));
         }
         let mut remainder = decimal_number;
         let mut component_collections: Vec<Vec<u8>> = vec![];
         loop {
-            let decomposition_step_result = get_max_components_from(remainder, base);
+            let decomposition_step_result = get_max_components_from(remainder, base)?;
             match decomposition_step_result {
                 None => break,
                 Some((transitive_remainder, components)) => {
@@ -46,38 +47,130 @@ impl Decomposition {
         })
     }
 
+    /// Components saturate at the type maximum;
+    /// a decomposition that passed `to_decimal` never reaches it.
     pub fn stream_all_components(&self) -> impl Iterator<Item = InputInt> + '_ {
         self.component_powers
             .iter()
-            .map(|power| self.calculate_single_component(*power))
+            .map(|power| (self.base as InputInt).saturating_pow(*power as u32))
     }
 
     /// Present the component as its regular integer variant
-    pub fn calculate_single_component(&self, component_power: u8) -> InputInt {
-        (self.base as InputInt).pow(component_power as u32)
+    pub fn calculate_single_component(&self, component_power: u8) -> Result<InputInt, SnicError> {
+        (self.base as InputInt)
+            .checked_pow(component_power as u32)
+            .ok_or(SnicError::Overflow)
     }
 
     /// Return the original integer value of the GBER.
-    pub fn to_decimal(&self) -> InputInt {
-        self.stream_all_components().sum::<InputInt>() + self.remainder as InputInt
+    pub fn to_decimal(&self) -> Result<InputInt, SnicError> {
+        self.component_powers
+            .iter()
+            .try_fold(self.remainder as InputInt, |total, power| {
+                total
+                    .checked_add(self.calculate_single_component(*power)?)
+                    .ok_or(SnicError::Overflow)
+            })
     }
 }
 
-fn get_max_components_from(number: InputInt, base: BaseInt) -> Option<(InputInt, Vec<u8>)> {
+fn get_max_components_from(
+    number: InputInt,
+    base: BaseInt,
+) -> Result<Option<(InputInt, Vec<u8>)>, SnicError> {
     // The log will always be >= 1,
     // since the size is always greater than the base
     if number < base.into() {
-        return None;
+        return Ok(None);
     }
     let exponent = common_utilities::integer_log(number, base);
 
     // Always: number >= component >= base > coefficient
-    let component = (base as InputInt).pow(exponent as u32);
-    let coefficient: BaseInt = (number / component) as BaseInt;
-    let full_term: InputInt = component * coefficient as InputInt;
+    let component = (base as InputInt)
+        .checked_pow(exponent as u32)
+        .ok_or(SnicError::Overflow)?;
+    let coefficient =
+        BaseInt::try_from(number / component).map_err(|_| SnicError::Overflow)?;
+    let full_term: InputInt = component
+        .checked_mul(coefficient as InputInt)
+        .ok_or(SnicError::Overflow)?;
     let temporary_remainder: InputInt = number - full_term;
-    Some((
+    Ok(Some((
         temporary_remainder,
         (0..coefficient).map(|_| exponent).collect(),
-    ))
+    )))
+}
+
+
+#[cfg(test)]
+mod test_decomposition {
+    use super::Decomposition;
+    use crate::common_types::InputInt;
+    use crate::error::SnicError;
+
+    #[test]
+    fn reject_small_bases() {
+        for base in [0, 1] {
+            assert_eq!(Decomposition::new(100, base), Err(SnicError::InvalidBase(base)));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for number in 0..1000 {
+            assert_eq!(Decomposition::new(number, 7).unwrap().to_decimal(), Ok(number));
+        }
+    }
+
+    #[test]
+    fn decompose_type_maximum() {
+        for base in [2, 3, 7, 10, 255, 256, 1000, u16::MAX] {
+            let decomposition = Decomposition::new(InputInt::MAX, base).unwrap();
+            assert_eq!(decomposition.to_decimal(), Ok(InputInt::MAX));
+        }
+    }
+
+    #[test]
+    fn decompose_exact_powers() {
+        for base in [3, 10] {
+            let mut power = base as InputInt;
+            let mut exponent = 1;
+            while let Some(next_power) = power.checked_mul(base as InputInt) {
+                let decomposition = Decomposition::new(power, base).unwrap();
+                assert_eq!(decomposition.component_powers, vec![exponent]);
+                assert_eq!(decomposition.remainder, 0);
+                power = next_power;
+                exponent += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn detect_component_overflow() {
+        let decomposition = Decomposition {
+            base: 2,
+            component_powers: vec![70],
+            remainder: 0,
+        };
+        assert_eq!(decomposition.calculate_single_component(70), Err(SnicError::Overflow));
+        assert_eq!(decomposition.to_decimal(), Err(SnicError::Overflow));
+    }
+
+    #[test]
+    fn detect_sum_overflow() {
+        let decomposition = Decomposition {
+            base: 2,
+            component_powers: vec![InputInt::BITS as u8 - 1; 2],
+            remainder: 1,
+        };
+        assert_eq!(decomposition.to_decimal(), Err(SnicError::Overflow));
+    }
+
+    #[cfg(feature = "u64-indices")]
+    #[test]
+    fn decompose_beyond_u32() {
+        let number = u32::MAX as InputInt * 3 + 17;
+        let decomposition = Decomposition::new(number, 10).unwrap();
+        assert_eq!(decomposition.to_decimal(), Ok(number));
+    }
 }
@@ -1,4 +1,6 @@
+pub mod catalog;
 pub mod common_types;
 pub mod common_utilities;
+pub mod error;
 pub mod gber;
 pub mod network;
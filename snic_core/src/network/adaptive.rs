@@ -0,0 +1,225 @@
+//! Matchups beyond the fixed plan, spent where the current ranking is least certain.
+//! The uncertainty of an item is the width of its bootstrapped rank interval,
+//! plus one for every neighbour in the current ranking it was never compared with.
+//! Items never compared at all are as uncertain as the network is large.
+use crate::common_types::{BaseInt, InputInt};
+use crate::error::SnicError;
+use crate::network::bootstrap::{self, BootstrapConfig};
+use crate::network::comparison::{self, pair_of, MatchupResult};
+use crate::network::rank::{self, Ranker};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveConfig {
+    /// Number of matchups to propose.
+    pub budget: usize,
+    pub bootstrap: BootstrapConfig,
+}
+
+impl AdaptiveConfig {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            bootstrap: BootstrapConfig::default(),
+        }
+    }
+
+    pub fn with_bootstrap(mut self, bootstrap: BootstrapConfig) -> Self {
+        self.bootstrap = bootstrap;
+        self
+    }
+}
+
+/// Propose matchups of `base` items, one per unit of budget.
+/// Each matchup is built around the most uncertain item left,
+/// with its nearest neighbours in the current ranking that it has not met yet.
+/// Items of a proposed matchup count as half as uncertain for the next ones.
+pub fn propose_adaptive_matchups<R: Ranker>(
+    ranker: &R,
+    network_size: InputInt,
+    base: BaseInt,
+    matchup_results: &[MatchupResult],
+    config: &AdaptiveConfig,
+) -> Result<Vec<Vec<InputInt>>, SnicError> {
+    if base < 2 {
+        return Err(SnicError::InvalidBase(base));
+    }
+    if network_size < base as InputInt {
+        return Err(SnicError::NetworkSmallerThanMatchup {
+            network_size,
+            matchup_size: base,
+        });
+    }
+    let ranking = rank::rank_interim(network_size, base, matchup_results, ranker)?;
+    let order = ranking
+        .standings
+        .iter()
+        .map(|standing| standing.item)
+        .collect::<Vec<InputInt>>();
+    let mut compared = comparison::compared_pairs(matchup_results);
+    let mut uncertainty = vec![network_size as f64; network_size as usize];
+    if !matchup_results.is_empty() {
+        let resampled =
+            bootstrap::bootstrap_ranking_from(ranker, matchup_results, &config.bootstrap)?;
+        for (item, (&(lowest, highest), &appearances)) in resampled
+            .rank_intervals
+            .iter()
+            .zip(&resampled.appearances)
+            .enumerate()
+        {
+            if appearances > 0 {
+                uncertainty[item] = (highest - lowest) as f64;
+            }
+        }
+    }
+    for neighbours in order.windows(2) {
+        if !compared.contains(&pair_of(neighbours[0], neighbours[1])) {
+            uncertainty[neighbours[0] as usize] += 1.0;
+            uncertainty[neighbours[1] as usize] += 1.0;
+        }
+    }
+
+    let mut position_of = vec![0; network_size as usize];
+    for (position, &item) in order.iter().enumerate() {
+        position_of[item as usize] = position;
+    }
+    let mut matchups = Vec::with_capacity(config.budget);
+    for _ in 0..config.budget {
+        // The first item wins ties, so proposals replay for the same results
+        let anchor = (0..network_size)
+            .max_by(|&left, &right| {
+                uncertainty[left as usize]
+                    .total_cmp(&uncertainty[right as usize])
+                    .then(right.cmp(&left))
+            })
+            .expect("the network holds a matchup");
+        let mut matchup = partners_of(anchor, &order, position_of[anchor as usize], base, &compared);
+        matchup.push(anchor);
+        matchup.sort_by_key(|&item| position_of[item as usize]);
+        for (position, &item) in matchup.iter().enumerate() {
+            uncertainty[item as usize] /= 2.0;
+            for &peer in &matchup[position + 1..] {
+                compared.insert(pair_of(item, peer));
+            }
+        }
+        matchups.push(matchup);
+    }
+    Ok(matchups)
+}
+
+/// The `base - 1` items closest to the anchor in the ranking,
+/// preferring the ones it has not been compared with.
+fn partners_of(
+    anchor: InputInt,
+    order: &[InputInt],
+    anchor_position: usize,
+    base: BaseInt,
+    compared: &HashSet<(InputInt, InputInt)>,
+) -> Vec<InputInt> {
+    let wanted = base as usize - 1;
+    let mut fresh = Vec::with_capacity(wanted);
+    let mut met = vec![];
+    for distance in 1..order.len() {
+        let above = anchor_position.checked_sub(distance).map(|position| order[position]);
+        let below = order.get(anchor_position + distance).copied();
+        for item in above.into_iter().chain(below) {
+            if compared.contains(&pair_of(anchor, item)) {
+                met.push(item);
+            } else if fresh.len() < wanted {
+                fresh.push(item);
+            }
+        }
+        if fresh.len() == wanted {
+            break;
+        }
+    }
+    let missing = wanted - fresh.len();
+    fresh.extend(met.into_iter().take(missing));
+    fresh
+}
+
+#[cfg(test)]
+mod test_adaptive {
+    use super::{propose_adaptive_matchups, AdaptiveConfig};
+    use crate::common_types::InputInt;
+    use crate::error::SnicError;
+    use crate::network::bootstrap::BootstrapConfig;
+    use crate::network::comparison::{self, MatchupResult};
+    use crate::network::rank::BradleyTerryRanker;
+    use std::collections::HashSet;
+
+    fn config(budget: usize) -> AdaptiveConfig {
+        AdaptiveConfig::new(budget).with_bootstrap(BootstrapConfig {
+            resamples: 30,
+            ..Default::default()
+        })
+    }
+
+    /// A strict chain over the first items, best item first.
+    fn chain(length: InputInt) -> Vec<MatchupResult> {
+        (0..length - 2)
+            .map(|item| MatchupResult::from(vec![item, item + 1, item + 2]))
+            .collect()
+    }
+
+    #[test]
+    fn spend_the_budget_on_valid_matchups() {
+        let ranker = BradleyTerryRanker::default();
+        let matchups = propose_adaptive_matchups(&ranker, 12, 3, &chain(12), &config(7)).unwrap();
+        assert_eq!(matchups.len(), 7);
+        for matchup in &matchups {
+            assert_eq!(matchup.len(), 3);
+            assert_eq!(matchup.iter().collect::<HashSet<_>>().len(), 3);
+            assert!(matchup.iter().all(|&item| item < 12));
+        }
+        let replayed = propose_adaptive_matchups(&ranker, 12, 3, &chain(12), &config(7)).unwrap();
+        assert_eq!(matchups, replayed);
+    }
+
+    #[test]
+    fn start_with_items_never_compared() {
+        let ranker = BradleyTerryRanker::default();
+        let matchups = propose_adaptive_matchups(&ranker, 12, 3, &chain(8), &config(2)).unwrap();
+        let proposed = matchups.iter().flatten().copied().collect::<HashSet<InputInt>>();
+        assert!((8..12).all(|item| proposed.contains(&item)), "{matchups:?}");
+    }
+
+    #[test]
+    fn reach_the_unknown_item_through_its_neighbours() {
+        // Every resample holds the only observed pair, so its ranks are certain
+        let results = vec![MatchupResult::from(vec![0, 1]); 5];
+        let ranker = BradleyTerryRanker::default();
+        let matchups = propose_adaptive_matchups(&ranker, 3, 2, &results, &config(2)).unwrap();
+        assert_eq!(matchups, vec![vec![1, 2], vec![0, 2]]);
+    }
+
+    #[test]
+    fn prefer_pairs_not_compared_yet() {
+        let ranker = BradleyTerryRanker::default();
+        let results = chain(12);
+        let mut compared = comparison::compared_pairs(&results);
+        for matchup in propose_adaptive_matchups(&ranker, 12, 3, &results, &config(5)).unwrap() {
+            let pairs = [(matchup[0], matchup[1]), (matchup[0], matchup[2]), (matchup[1], matchup[2])]
+                .map(|(left, right)| comparison::pair_of(left, right));
+            assert!(pairs.iter().any(|pair| !compared.contains(pair)), "{matchup:?}");
+            compared.extend(pairs);
+        }
+    }
+
+    #[test]
+    fn propose_without_results() {
+        let ranker = BradleyTerryRanker::default();
+        // Without results, the proposals chain every item together
+        let matchups = propose_adaptive_matchups(&ranker, 9, 3, &[], &config(4)).unwrap();
+        assert_eq!(matchups, vec![vec![0, 1, 2], vec![2, 3, 4], vec![4, 5, 6], vec![6, 7, 8]]);
+    }
+
+    #[test]
+    fn reject_networks_smaller_than_a_matchup() {
+        let ranker = BradleyTerryRanker::default();
+        assert!(matches!(
+            propose_adaptive_matchups(&ranker, 2, 3, &[], &config(1)),
+            Err(SnicError::NetworkSmallerThanMatchup { .. })
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+//! Uncertainty of a ranking, estimated by resampling the ranked matchups.
+//! Every resample draws as many matchups as were ranked, with replacement,
+//! and is ranked again with the same model.
+//! Scores of all resamples are kept, so memory grows with resamples × items.
+use crate::common_types::InputInt;
+use crate::common_utilities::SplitMix64;
+use crate::error::SnicError;
+use crate::network::comparison::{self, MatchupResult};
+use crate::network::rank::{Ranker, RankingOutcome};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BootstrapConfig {
+    pub resamples: usize,
+    /// Coverage of the central intervals, between 0 and 1.
+    pub confidence: f64,
+    pub seed: u64,
+}
+
+impl BootstrapConfig {
+    pub const DEFAULT_RESAMPLES: usize = 200;
+    pub const DEFAULT_CONFIDENCE: f64 = 0.95;
+}
+
+impl Default for BootstrapConfig {
+    fn default() -> Self {
+        Self {
+            resamples: Self::DEFAULT_RESAMPLES,
+            confidence: Self::DEFAULT_CONFIDENCE,
+            seed: 0,
+        }
+    }
+}
+
+/// Point ranking with per-item intervals, all indexed by item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootstrapOutcome {
+    /// The ranking of the matchups as given.
+    pub point: RankingOutcome,
+    pub score_intervals: Vec<(f32, f32)>,
+    /// Rank positions, 0 being the strongest item.
+    pub rank_intervals: Vec<(InputInt, InputInt)>,
+    /// Resamples in which the item took part at all.
+    pub appearances: Vec<usize>,
+}
+
+pub fn bootstrap_ranking_from<R: Ranker + ?Sized>(
+    ranker: &R,
+    matchup_results: &[MatchupResult],
+    config: &BootstrapConfig,
+) -> Result<BootstrapOutcome, SnicError> {
+    if config.resamples == 0 || !(0.0..=1.0).contains(&config.confidence) {
+        return Err(SnicError::InvalidParameter(
+            "bootstrapping needs resamples and a confidence between 0 and 1".into(),
+        ));
+    }
+    let point = ranker.rank(&comparison::expand_matchup_results(matchup_results)?);
+    let node_count = point.scores.len();
+    let mut generator = SplitMix64::new(config.seed);
+    let mut score_samples: Vec<Vec<f32>> = vec![Vec::with_capacity(config.resamples); node_count];
+    let mut rank_samples: Vec<Vec<InputInt>> = vec![Vec::with_capacity(config.resamples); node_count];
+    let mut present = vec![false; node_count];
+    for _ in 0..config.resamples {
+        let resample: Vec<MatchupResult> = (0..matchup_results.len())
+            .map(|_| matchup_results[generator.next_below(matchup_results.len() as u64) as usize].clone())
+            .collect();
+        present.iter_mut().for_each(|present| *present = false);
+        resample
+            .iter()
+            .flat_map(MatchupResult::items)
+            .for_each(|item| present[item as usize] = true);
+        let scores = ranker.rank(&comparison::expand_matchup_results(&resample)?).scores;
+        let mut contenders: Vec<usize> = (0..scores.len()).filter(|&item| present[item]).collect();
+        contenders.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+        for (position, item) in contenders.into_iter().enumerate() {
+            score_samples[item].push(scores[item]);
+            rank_samples[item].push(position as InputInt);
+        }
+    }
+    let tail = (1.0 - config.confidence) / 2.0;
+    Ok(BootstrapOutcome {
+        point,
+        appearances: score_samples.iter().map(Vec::len).collect(),
+        score_intervals: score_samples
+            .into_iter()
+            .map(|samples| central_interval_of(samples, tail, f32::NAN))
+            .collect(),
+        rank_intervals: rank_samples
+            .into_iter()
+            .map(|samples| central_interval_of(samples, tail, InputInt::MAX))
+            .collect(),
+    })
+}
+
+/// Empirical quantiles cutting the tail share off both ends.
+fn central_interval_of<T: Copy + PartialOrd>(mut samples: Vec<T>, tail: f64, missing: T) -> (T, T) {
+    if samples.is_empty() {
+        return (missing, missing);
+    }
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let last = samples.len() - 1;
+    let lower = (tail * last as f64).floor() as usize;
+    let upper = ((1.0 - tail) * last as f64).ceil() as usize;
+    (samples[lower], samples[upper.min(last)])
+}
+
+
+#[cfg(test)]
+mod test_bootstrap {
+    use super::{bootstrap_ranking_from, central_interval_of, BootstrapConfig};
+    use crate::common_types::InputInt;
+    use crate::error::SnicError;
+    use crate::network::comparison::MatchupResult;
+    use crate::network::rank::BradleyTerryRanker;
+
+    fn chain_matchups() -> Vec<MatchupResult> {
+        // A strict order with one contested pair in the middle
+        let mut matchups: Vec<Vec<InputInt>> =
+            vec![vec![0, 1, 2], vec![0, 1, 3], vec![1, 2, 3], vec![0, 2, 3]];
+        matchups.extend([vec![0, 2, 1], vec![3, 2, 1]]);
+        matchups.into_iter().map(MatchupResult::from).cycle().take(60).collect()
+    }
+
+    #[test]
+    fn replay_from_seed() {
+        let ranker = BradleyTerryRanker::default();
+        let config = BootstrapConfig {
+            resamples: 20,
+            ..Default::default()
+        };
+        let first = bootstrap_ranking_from(&ranker, &chain_matchups(), &config).unwrap();
+        let second = bootstrap_ranking_from(&ranker, &chain_matchups(), &config).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn intervals_cover_point_ranking() {
+        let ranker = BradleyTerryRanker::default();
+        let outcome =
+            bootstrap_ranking_from(&ranker, &chain_matchups(), &BootstrapConfig::default()).unwrap();
+        assert_eq!(outcome.rank_intervals[0], (0, 0));
+        for (item, score) in outcome.point.scores.iter().enumerate() {
+            let (lower, upper) = outcome.score_intervals[item];
+            assert!(lower <= *score && *score <= upper);
+            assert_eq!(outcome.appearances[item], BootstrapConfig::DEFAULT_RESAMPLES);
+        }
+        let (lower, upper) = outcome.rank_intervals[2];
+        assert!(lower < upper, "the contested item should not have a settled rank");
+    }
+
+    #[test]
+    fn reject_degenerate_config() {
+        for config in [
+            BootstrapConfig { resamples: 0, ..Default::default() },
+            BootstrapConfig { confidence: 1.5, ..Default::default() },
+        ] {
+            assert!(matches!(
+                bootstrap_ranking_from(&BradleyTerryRanker::default(), &chain_matchups(), &config),
+                Err(SnicError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn quantiles_of_a_range() {
+        let samples: Vec<u32> = (0..=100).collect();
+        assert_eq!(central_interval_of(samples, 0.05, 0), (5, 95));
+        assert_eq!(central_interval_of(Vec::<u32>::new(), 0.05, 7), (7, 7));
+    }
+}
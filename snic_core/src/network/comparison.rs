@@ -1,4 +1,6 @@
-use crate::common_types::BaseInt;
+use crate::common_types::{BaseInt, InputInt};
+use crate::error::SnicError;
+use std::collections::HashSet;
 
 /// Generate pairwise combinations of indices for given size
 pub fn generate_expansion_mould_for(size: BaseInt) -> Vec<(BaseInt, BaseInt)> {
@@ -26,6 +28,511 @@ pub fn convert_to_comparisons<T: Clone>(
 }
 
 
+/// Pairwise outcome between two items of a matchup.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub winner: InputInt,
+    pub loser: InputInt,
+    /// The items were declared equal, so the sides are interchangeable.
+    pub tie: bool,
+    /// How much the outcome counts: judge confidence, margin or repetitions.
+    pub weight: f32,
+}
+
+impl Comparison {
+    pub fn tie_between(left: InputInt, right: InputInt) -> Self {
+        Self {
+            winner: left,
+            loser: right,
+            tie: true,
+            weight: 1.0,
+        }
+    }
+
+    pub fn with_weight(self, weight: f32) -> Self {
+        Self { weight, ..self }
+    }
+}
+
+impl From<(InputInt, InputInt)> for Comparison {
+    fn from((winner, loser): (InputInt, InputInt)) -> Self {
+        Self {
+            winner,
+            loser,
+            tie: false,
+            weight: 1.0,
+        }
+    }
+}
+
+
+/// Ranked matchup that allows ties and truncated orderings.
+/// Tiers run from best to worst and the items within a tier are equal.
+/// Unranked items sit below every tier, in an unknown order among themselves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchupResult {
+    pub tiers: Vec<Vec<InputInt>>,
+    pub unranked: Vec<InputInt>,
+    /// Carried over to every comparison of the matchup, must be positive.
+    pub weight: f32,
+}
+
+impl Default for MatchupResult {
+    fn default() -> Self {
+        Self {
+            tiers: vec![],
+            unranked: vec![],
+            weight: 1.0,
+        }
+    }
+}
+
+impl MatchupResult {
+    pub fn items(&self) -> impl Iterator<Item = InputInt> + '_ {
+        self.tiers.iter().flatten().chain(&self.unranked).copied()
+    }
+
+    /// Ties within tiers, wins across tiers and over the unranked items.
+    /// Unranked items are not compared with each other.
+    pub fn to_comparisons(&self) -> Vec<Comparison> {
+        let mut comparisons = vec![];
+        for (tier_position, tier) in self.tiers.iter().enumerate() {
+            for (item_position, &item) in tier.iter().enumerate() {
+                comparisons.extend(
+                    tier[item_position + 1..]
+                        .iter()
+                        .map(|&peer| Comparison::tie_between(item, peer).with_weight(self.weight)),
+                );
+                comparisons.extend(
+                    self.tiers[tier_position + 1..]
+                        .iter()
+                        .flatten()
+                        .chain(&self.unranked)
+                        .map(|&loser| Comparison::from((item, loser)).with_weight(self.weight)),
+                );
+            }
+        }
+        comparisons
+    }
+
+    pub(crate) fn validate(&self, position: usize) -> Result<(), SnicError> {
+        if self.tiers.iter().any(Vec::is_empty) {
+            return Err(SnicError::InvalidRankings(format!(
+                "matchup {position} has an empty tier"
+            )));
+        }
+        let items = self.items().collect::<Vec<InputInt>>();
+        if items.len() < 2 || self.tiers.is_empty() {
+            return Err(SnicError::InvalidRankings(format!(
+                "matchup {position} ranks nothing among its items"
+            )));
+        }
+        check_weight(position, self.weight)?;
+        check_distinct_items(position, &items)
+    }
+}
+
+impl From<Vec<InputInt>> for MatchupResult {
+    /// Strict ordering, best item first.
+    fn from(ranking: Vec<InputInt>) -> Self {
+        Self {
+            tiers: ranking.into_iter().map(|item| vec![item]).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+
+/// Expand matchup results with ties or truncated orderings into comparisons.
+pub fn expand_matchup_results(results: &[MatchupResult]) -> Result<Vec<Comparison>, SnicError> {
+    if results.is_empty() {
+        return Err(SnicError::InvalidRankings("no ranked matchups given".into()));
+    }
+    let mut comparisons = vec![];
+    for (position, result) in results.iter().enumerate() {
+        result.validate(position)?;
+        comparisons.extend(result.to_comparisons());
+    }
+    Ok(comparisons)
+}
+
+/// Unordered pair of items, the lower item first.
+pub fn pair_of(left: InputInt, right: InputInt) -> (InputInt, InputInt) {
+    (left.min(right), left.max(right))
+}
+
+/// Pairs of items compared at least once in the results, as `pair_of` gives them.
+pub fn compared_pairs(results: &[MatchupResult]) -> HashSet<(InputInt, InputInt)> {
+    results
+        .iter()
+        .flat_map(MatchupResult::to_comparisons)
+        .map(|comparison| pair_of(comparison.winner, comparison.loser))
+        .collect()
+}
+
+
+/// Per-item counts of the comparisons behind a ranking, indexed by item.
+/// Ties count as comparisons but neither as wins nor as losses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComparisonTally {
+    pub comparisons: Vec<u64>,
+    pub wins: Vec<u64>,
+    pub losses: Vec<u64>,
+}
+
+impl ComparisonTally {
+    pub fn from_comparisons(comparisons: &[Comparison]) -> Self {
+        let mut tally = Self::default();
+        for comparison in comparisons {
+            let decided = !comparison.tie as u64;
+            tally.record(comparison.winner, 1, decided, 0);
+            tally.record(comparison.loser, 1, 0, decided);
+        }
+        tally
+    }
+
+    /// Tally strict orderings without expanding them into comparisons.
+    pub fn from_ranked_matchups<R: AsRef<[InputInt]>>(ranked_matchups: &[R]) -> Self {
+        let mut tally = Self::default();
+        for ranking in ranked_matchups {
+            let ranking = ranking.as_ref();
+            for (position, &item) in ranking.iter().enumerate() {
+                let wins = (ranking.len() - position - 1) as u64;
+                tally.record(item, wins + position as u64, wins, position as u64);
+            }
+        }
+        tally
+    }
+
+    pub fn len(&self) -> usize {
+        self.comparisons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.comparisons.is_empty()
+    }
+
+    fn record(&mut self, item: InputInt, comparisons: u64, wins: u64, losses: u64) {
+        let index = item as usize;
+        if index >= self.len() {
+            for counts in [&mut self.comparisons, &mut self.wins, &mut self.losses] {
+                counts.resize(index + 1, 0);
+            }
+        }
+        self.comparisons[index] += comparisons;
+        self.wins[index] += wins;
+        self.losses[index] += losses;
+    }
+}
+
+
+/// Retrieve elements by pairwise indices mould, weighting every comparison the same.
+pub fn convert_to_weighted_comparisons(
+    matchup_result: &[InputInt],
+    mould: &[(BaseInt, BaseInt)],
+    weight: f32,
+) -> Vec<Comparison> {
+    convert_to_comparisons(matchup_result, mould)
+        .into_iter()
+        .map(|pair| Comparison::from(pair).with_weight(weight))
+        .collect()
+}
+
+
+/// Expand ranked matchups into pairwise comparisons.
+/// Every matchup must list the same number of distinct items, at least two.
+/// Matchups may be vectors or borrowed rows.
+pub fn expand_ranked_matchups<R: AsRef<[InputInt]>>(
+    ranked_matchups: &[R],
+) -> Result<Vec<Comparison>, SnicError> {
+    expand_matchups_with(ranked_matchups, std::iter::repeat(1.0))
+}
+
+
+/// Expand ranked matchups with one weight per matchup into pairwise comparisons.
+/// Weights must be positive and finite; leave out a matchup instead of zeroing it.
+pub fn expand_weighted_matchups<R: AsRef<[InputInt]>>(
+    ranked_matchups: &[R],
+    weights: &[f32],
+) -> Result<Vec<Comparison>, SnicError> {
+    check_weights(ranked_matchups.len(), weights)?;
+    expand_matchups_with(ranked_matchups, weights.iter().copied())
+}
+
+
+fn expand_matchups_with<R: AsRef<[InputInt]>>(
+    ranked_matchups: &[R],
+    weights: impl Iterator<Item = f32>,
+) -> Result<Vec<Comparison>, SnicError> {
+    let matchup_size = matchup_size_of(ranked_matchups)?;
+    let expansion_mould = generate_expansion_mould_for(matchup_size);
+    let mut comparisons = Vec::with_capacity(ranked_matchups.len() * expansion_mould.len());
+    for ((position, ranking), weight) in ranked_matchups.iter().enumerate().zip(weights) {
+        let ranking = ranking.as_ref();
+        check_ranked_matchup(position, ranking, matchup_size)?;
+        comparisons.extend(convert_to_weighted_comparisons(ranking, &expansion_mould, weight));
+    }
+    Ok(comparisons)
+}
+
+
+/// Expand ranked matchups on all threads, creating one value per (winner, loser) pair
+/// from the position of its matchup and both items.
+/// Every thread keeps the values it created in a list of its own,
+/// so they are never gathered into a single one.
+/// Validates like `expand_ranked_matchups`, though any of several malformed matchups
+/// may be the one reported.
+#[cfg(feature = "parallel")]
+pub(crate) fn par_expand_ranked_matchups_in_chunks_with<R: AsRef<[InputInt]> + Sync, T: Send>(
+    ranked_matchups: &[R],
+    create: impl Fn(usize, InputInt, InputInt) -> T + Sync,
+) -> Result<Vec<Vec<T>>, SnicError> {
+    use rayon::prelude::*;
+    let expansion_mould = par_expansion_mould_of(ranked_matchups)?;
+    Ok(ranked_matchups
+        .par_iter()
+        .enumerate()
+        .fold(Vec::new, |mut chunk, (position, ranking)| {
+            let ranking = ranking.as_ref();
+            chunk.extend(expansion_mould.iter().map(|&(lix, rix)| {
+                create(position, ranking[lix as usize], ranking[rix as usize])
+            }));
+            chunk
+        })
+        .collect())
+}
+
+
+/// Expansion mould of the matchups, once all of them are checked on all threads.
+#[cfg(feature = "parallel")]
+fn par_expansion_mould_of<R: AsRef<[InputInt]> + Sync>(
+    ranked_matchups: &[R],
+) -> Result<Vec<(BaseInt, BaseInt)>, SnicError> {
+    use rayon::prelude::*;
+    let matchup_size = matchup_size_of(ranked_matchups)?;
+    ranked_matchups
+        .par_iter()
+        .enumerate()
+        .try_for_each(|(position, ranking)| {
+            check_ranked_matchup(position, ranking.as_ref(), matchup_size)
+        })?;
+    Ok(generate_expansion_mould_for(matchup_size))
+}
+
+
+/// Common size of the matchups, taken from the first one.
+fn matchup_size_of<R: AsRef<[InputInt]>>(ranked_matchups: &[R]) -> Result<BaseInt, SnicError> {
+    let matchup_size = ranked_matchups
+        .first()
+        .ok_or_else(|| SnicError::InvalidRankings("no ranked matchups given".into()))?
+        .as_ref()
+        .len();
+    if matchup_size < 2 {
+        return Err(SnicError::InvalidRankings(
+            "a matchup must rank at least two items".into(),
+        ));
+    }
+    BaseInt::try_from(matchup_size).map_err(|_| SnicError::Overflow)
+}
+
+
+fn check_ranked_matchup(
+    position: usize,
+    ranking: &[InputInt],
+    matchup_size: BaseInt,
+) -> Result<(), SnicError> {
+    if ranking.len() != matchup_size as usize {
+        return Err(SnicError::InvalidRankings(format!(
+            "matchup {position} ranks {} items instead of {matchup_size}",
+            ranking.len()
+        )));
+    }
+    check_distinct_items(position, ranking)
+}
+
+
+/// One positive weight per matchup.
+pub(crate) fn check_weights(matchup_count: usize, weights: &[f32]) -> Result<(), SnicError> {
+    if weights.len() != matchup_count {
+        return Err(SnicError::InvalidRankings(format!(
+            "{} weights given for {matchup_count} matchups",
+            weights.len()
+        )));
+    }
+    weights
+        .iter()
+        .enumerate()
+        .try_for_each(|(position, &weight)| check_weight(position, weight))
+}
+
+fn check_weight(position: usize, weight: f32) -> Result<(), SnicError> {
+    if !weight.is_finite() || weight <= 0.0 {
+        return Err(SnicError::InvalidRankings(format!(
+            "matchup {position} has a weight of {weight}, weights must be positive"
+        )));
+    }
+    Ok(())
+}
+
+
+pub(crate) fn check_distinct_items(position: usize, ranking: &[InputInt]) -> Result<(), SnicError> {
+    if ranking.iter().collect::<HashSet<_>>().len() != ranking.len() {
+        return Err(SnicError::InvalidRankings(format!(
+            "matchup {position} ranks the same item twice"
+        )));
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod test_ranked_matchups_expansion {
+    use super::{
+        expand_matchup_results, expand_ranked_matchups, expand_weighted_matchups, Comparison,
+        MatchupResult,
+    };
+    use crate::error::SnicError;
+
+    #[test]
+    fn expand_in_order() {
+        let expected = [(2, 0), (2, 1), (0, 1), (3, 4), (3, 5), (4, 5)]
+            .into_iter().map(Comparison::from).collect::<Vec<Comparison>>();
+        assert_eq!(expand_ranked_matchups(&[vec![2, 0, 1], vec![3, 4, 5]]), Ok(expected.clone()));
+        let results = [vec![2, 0, 1].into(), vec![3, 4, 5].into()];
+        assert_eq!(expand_matchup_results(&results), Ok(expected));
+    }
+
+    #[test]
+    fn expand_ties_and_truncation() {
+        let result = MatchupResult {
+            tiers: vec![vec![4], vec![1, 2]],
+            unranked: vec![0, 3],
+            ..Default::default()
+        };
+        assert_eq!(
+            result.to_comparisons(),
+            vec![
+                Comparison::from((4, 1)),
+                Comparison::from((4, 2)),
+                Comparison::from((4, 0)),
+                Comparison::from((4, 3)),
+                Comparison::tie_between(1, 2),
+                Comparison::from((1, 0)),
+                Comparison::from((1, 3)),
+                Comparison::from((2, 0)),
+                Comparison::from((2, 3)),
+            ],
+        );
+    }
+
+    #[test]
+    fn carry_weights() {
+        let weighted = expand_weighted_matchups(&[vec![0, 1, 2], vec![3, 4, 5]], &[0.5, 2.0]).unwrap();
+        assert_eq!(
+            weighted.iter().map(|comparison| comparison.weight).collect::<Vec<f32>>(),
+            vec![0.5, 0.5, 0.5, 2.0, 2.0, 2.0],
+        );
+        let result = MatchupResult {
+            tiers: vec![vec![0, 1]],
+            unranked: vec![2],
+            weight: 3.0,
+        };
+        assert!(result.to_comparisons().iter().all(|comparison| comparison.weight == 3.0));
+    }
+
+    #[test]
+    fn reject_malformed_weights() {
+        let matchups = [vec![0, 1], vec![1, 2]];
+        for weights in [vec![1.0], vec![1.0, -1.0], vec![0.0, 1.0], vec![f32::NAN, 1.0]] {
+            assert!(matches!(
+                expand_weighted_matchups(&matchups, &weights),
+                Err(SnicError::InvalidRankings(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn reject_malformed_results() {
+        let malformed = [
+            MatchupResult { tiers: vec![vec![0, 1], vec![]], ..Default::default() },
+            MatchupResult { unranked: vec![0, 1], ..Default::default() },
+            MatchupResult { tiers: vec![vec![0]], ..Default::default() },
+            MatchupResult { tiers: vec![vec![0]], unranked: vec![0], ..Default::default() },
+            MatchupResult { tiers: vec![vec![0, 1]], weight: -1.0, ..Default::default() },
+            MatchupResult { tiers: vec![vec![0, 1]], weight: 0.0, ..Default::default() },
+        ];
+        for result in malformed {
+            assert!(matches!(
+                expand_matchup_results(&[result]),
+                Err(SnicError::InvalidRankings(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn reject_malformed_rankings() {
+        for rankings in [vec![], vec![vec![1]], vec![vec![0, 1], vec![2, 3, 4]], vec![vec![1, 1]]] {
+            assert!(matches!(
+                expand_ranked_matchups(&rankings),
+                Err(SnicError::InvalidRankings(_))
+            ));
+        }
+    }
+}
+
+
+#[cfg(all(test, feature = "parallel"))]
+mod test_parallel_expansion {
+    use super::{expand_ranked_matchups, par_expand_ranked_matchups_in_chunks_with, Comparison};
+    use crate::common_types::InputInt;
+    use crate::error::SnicError;
+
+    /// Flattened and sorted, since threads split the matchups differently on every run.
+    fn par_expand_ranked_matchups(
+        ranked_matchups: &[Vec<InputInt>],
+    ) -> Result<Vec<Comparison>, SnicError> {
+        let chunks = par_expand_ranked_matchups_in_chunks_with(ranked_matchups, |_, winner, loser| {
+            Comparison::from((winner, loser))
+        })?;
+        let mut comparisons = chunks.concat();
+        comparisons.sort_by_key(|comparison| (comparison.winner, comparison.loser));
+        Ok(comparisons)
+    }
+
+    #[test]
+    fn match_serial_expansion() {
+        let ranked_matchups = (0..10_000)
+            .map(|head| vec![head * 3 + 2, head * 3, head * 3 + 1])
+            .collect::<Vec<_>>();
+        let mut comparisons = expand_ranked_matchups(&ranked_matchups).unwrap();
+        comparisons.sort_by_key(|comparison| (comparison.winner, comparison.loser));
+        assert_eq!(par_expand_ranked_matchups(&ranked_matchups).unwrap(), comparisons);
+    }
+
+    #[test]
+    fn pass_matchup_positions() {
+        let ranked_matchups = vec![vec![0, 1], vec![2, 3], vec![4, 5]];
+        let chunks = par_expand_ranked_matchups_in_chunks_with(&ranked_matchups, |position, winner, _| {
+            (position, winner)
+        })
+        .unwrap();
+        let mut created = chunks.concat();
+        created.sort_unstable();
+        assert_eq!(created, vec![(0, 0), (1, 2), (2, 4)]);
+    }
+
+    #[test]
+    fn reject_malformed_matchups() {
+        for ranked_matchups in [vec![], vec![vec![0, 1], vec![2, 2]], vec![vec![0, 1], vec![2]]] {
+            assert!(matches!(
+                par_expand_ranked_matchups(&ranked_matchups),
+                Err(SnicError::InvalidRankings(_))
+            ));
+        }
+    }
+}
+
+
 #[cfg(test)]
 mod test_matchup_result_conversion {
     use super::convert_to_comparisons;
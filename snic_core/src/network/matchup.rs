@@ -1,32 +1,58 @@
 //! Generation of matchups for a network based on a GBER of it's size.
 use crate::common_types::{BaseInt, InputInt};
-use crate::common_utilities;
+use crate::common_utilities::{self, SeededPermutation};
+use crate::error::SnicError;
 use crate::gber;
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
-use std::iter::zip;
+use std::iter::{zip, FusedIterator};
 
 /// Calculate the number of matchups for a subnetwork of a given size.
 /// Since it can be derived mathematically, this is more useful to do
 /// than to iterate through the matchups.
-pub fn calculate_matchups_number_for(subnetwork_size: InputInt, base: BaseInt) -> u64 {
-    let initiator_items: u64 = (subnetwork_size / base as InputInt).into();
+pub fn calculate_matchups_number_for(
+    subnetwork_size: InputInt,
+    base: BaseInt,
+) -> Result<u64, SnicError> {
+    let initiator_items: InputInt = subnetwork_size / base as InputInt;
     let matchups_per_item = common_utilities::integer_log(subnetwork_size, base);
-    initiator_items * matchups_per_item as u64
+    (initiator_items as u64)
+        .checked_mul(matchups_per_item as u64)
+        .ok_or(SnicError::Overflow)
 }
 
 /// Calculate the number of comparisons for a given number of matchups.
 /// Since the number of comparisons is a direct function of the number of matchups,
 /// this is more useful to do than to iterate through the matchups.
-pub fn calculate_comparisons_number_for(matchups_number: u64, base: BaseInt) -> u64 {
-    let base: InputInt = base.into();
-    let per_matchup: InputInt = base * (base - 1) / 2;
-    matchups_number * per_matchup as u64
+pub fn calculate_comparisons_number_for(
+    matchups_number: u64,
+    base: BaseInt,
+) -> Result<u64, SnicError> {
+    let base = base as u64;
+    let per_matchup = base * base.saturating_sub(1) / 2;
+    matchups_number
+        .checked_mul(per_matchup)
+        .ok_or(SnicError::Overflow)
+}
+
+/// Serializable position of a subnetwork iterator.
+/// The state of the iterator is derived from the number of yielded matchups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubnetworkCursor {
+    pub network_size: InputInt,
+    pub matchup_size: BaseInt,
+    pub offset: InputInt,
+    /// Matchups yielded so far.
+    pub position: u64,
 }
 
 /// Iterator for generating the matchups of a subnetwork.
 /// References elements of the subnetwork by their index.
 /// Indices are 0-based and global to the whole network.
-#[derive(Default, Debug)]
+/// Subnetworks sized as a power of the base, which every network plan is made of,
+/// derive each matchup from its position and hold constant state.
+/// Other sizes walk the sections of the current level, one cursor per section.
+#[derive(Default, Debug, Clone)]
 pub struct SubnetworkMatchupIterator {
     /// Should be an integer component of a GBER term.
     pub network_size: InputInt,
@@ -34,10 +60,13 @@ pub struct SubnetworkMatchupIterator {
     pub matchup_size: BaseInt,
     /// Index offset for the items in the subnetwork.
     pub offset: InputInt,
+    uniform_levels: bool,
+    total_matchups: u64,
     level: u8,
     // Current item, exclusive end item
     section_states: Vec<(InputInt, InputInt)>,
     section_group_head: usize,
+    yielded_matchups: u64,
 }
 
 impl SubnetworkMatchupIterator {
@@ -45,20 +74,125 @@ impl SubnetworkMatchupIterator {
         network_size: InputInt,
         matchup_size: BaseInt,
         offset: InputInt,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, SnicError> {
+        if matchup_size < 2 {
+            return Err(SnicError::InvalidBase(matchup_size));
+        }
+        if network_size == 0 {
+            return Err(SnicError::EmptyNetwork);
+        }
+        if network_size < matchup_size.into() {
+            return Err(SnicError::NetworkSmallerThanMatchup {
+                network_size,
+                matchup_size,
+            });
+        }
+        let depth = common_utilities::integer_log(network_size, matchup_size);
         Ok(Self {
             network_size,
             matchup_size,
             offset,
+            total_matchups: calculate_matchups_number_for(network_size, matchup_size)?,
+            uniform_levels: (matchup_size as InputInt).checked_pow(depth as u32)
+                == Some(network_size),
             ..Default::default()
         })
     }
+
+    /// Resume right after the matchups already yielded at the cursor.
+    /// Only subnetworks sized as a power of the base can be resumed.
+    pub fn resume(cursor: &SubnetworkCursor) -> Result<Self, SnicError> {
+        let mut iterator = Self::new(cursor.network_size, cursor.matchup_size, cursor.offset)?;
+        if !iterator.uniform_levels {
+            return Err(SnicError::InvalidParameter(format!(
+                "a subnetwork of {} items cannot be resumed with base {}",
+                cursor.network_size, cursor.matchup_size
+            )));
+        }
+        let total = iterator.total_matchups;
+        if cursor.position > total {
+            return Err(SnicError::InvalidParameter(format!(
+                "cursor position {} is past the {total} matchups of the subnetwork",
+                cursor.position
+            )));
+        }
+        iterator.yielded_matchups = cursor.position;
+        Ok(iterator)
+    }
+
+    /// Matchups of the whole subnetwork, however many were yielded.
+    pub fn total_matchups(&self) -> u64 {
+        self.total_matchups
+    }
+
+    pub fn cursor(&self) -> SubnetworkCursor {
+        SubnetworkCursor {
+            network_size: self.network_size,
+            matchup_size: self.matchup_size,
+            offset: self.offset,
+            position: self.yielded_matchups,
+        }
+    }
+
+    /// Matchup at a position counted from the start of the subnetwork,
+    /// regardless of how far the iteration went.
+    /// Only subnetworks sized as a power of the base have positions.
+    pub fn matchup_at(&self, position: u64) -> Option<Vec<InputInt>> {
+        if !self.uniform_levels || position >= self.total_matchups
+        {
+            return None;
+        }
+        let base = self.matchup_size as InputInt;
+        let (level, level_position) = self.split_position(position);
+        let section_size: InputInt = self.network_size / base.pow(level);
+        let group_position = (level_position % section_size as u64) as InputInt;
+        let group_head = (level_position / section_size as u64) as InputInt * base;
+        Some(
+            (group_head..group_head + base)
+                .map(|section| section * section_size + group_position + self.offset)
+                .collect(),
+        )
+    }
+
+    /// Positions of the matchups holding an item, one per level, ascending.
+    /// Empty for items outside of the subnetwork.
+    pub fn matchups_containing(&self, item: InputInt) -> Vec<u64> {
+        if !self.uniform_levels
+            || item < self.offset
+            || item - self.offset >= self.network_size
+        {
+            return vec![];
+        }
+        let base = self.matchup_size as InputInt;
+        let local_item = item - self.offset;
+        let initiator_items: InputInt = self.network_size / base;
+        let depth = common_utilities::integer_log(self.network_size, self.matchup_size) as u32;
+        (1..=depth)
+            .map(|level| {
+                let section_size: InputInt = self.network_size / base.pow(level);
+                let group: InputInt = local_item / section_size / base;
+                let group_position: InputInt = local_item % section_size;
+                (level - 1) as u64 * initiator_items as u64
+                    + group as u64 * section_size as u64
+                    + group_position as u64
+            })
+            .collect()
+    }
+
+    /// Level, counted from 1, and the position within it.
+    fn split_position(&self, position: u64) -> (u32, u64) {
+        let initiator_items: InputInt = self.network_size / self.matchup_size as InputInt;
+        let level_matchups = initiator_items as u64;
+        ((position / level_matchups) as u32 + 1, position % level_matchups)
+    }
+
+    /// Stays on the last level once the sections run out, so the iterator is fused.
     fn init_level(&mut self) -> Option<()> {
-        self.level += 1;
-        let sections_number = self.calculate_sections_number();
+        let sections_number = self.calculate_sections_number()?;
         if sections_number > self.network_size {
             return None;
         }
+        self.level += 1;
         self.section_states.clear();
         let section_size: InputInt = self.network_size / sections_number;
         let mut head: InputInt = 0;
@@ -74,11 +208,10 @@ impl SubnetworkMatchupIterator {
         Some(())
     }
 
-    fn calculate_sections_number(&self) -> InputInt {
-        (self.matchup_size as InputInt).pow(self.level as InputInt)
-    }
-    fn increment_internal_section_pointer(&mut self, index: usize) {
-        self.section_states[index].0 += 1;
+    /// Sections of the next level.
+    /// Past the type maximum there are more sections than items anyway.
+    fn calculate_sections_number(&self) -> Option<InputInt> {
+        (self.matchup_size as InputInt).checked_pow(self.level as u32 + 1)
     }
     fn increment_group_head_if_exhausted(&mut self) {
         let (ctrlhead, ctrltail) = self.section_states[self.section_group_head];
@@ -92,69 +225,535 @@ impl Iterator for SubnetworkMatchupIterator {
     type Item = Vec<InputInt>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.uniform_levels {
+            let matchup = self.matchup_at(self.yielded_matchups)?;
+            self.yielded_matchups += 1;
+            return Some(matchup);
+        }
         if self.section_group_head >= self.section_states.len() {
             self.init_level()?
         };
         let group_size = self.matchup_size as usize;
-        let sections = self.section_states.clone();
-
-        let selected_sections = sections
-            .iter()
-            .enumerate()
+        let offset = self.offset;
+        let matchup: Vec<InputInt> = self
+            .section_states
+            .iter_mut()
             .skip(self.section_group_head)
-            .take(group_size);
-        let matchup: Vec<InputInt> = selected_sections
-            .map(|(ix, section)| {
+            .take(group_size)
+            .map(|section| {
                 let old_head = section.0;
-                self.increment_internal_section_pointer(ix);
-                old_head + self.offset
+                section.0 += 1;
+                old_head + offset
             })
             .collect();
         self.increment_group_head_if_exhausted();
+        self.yielded_matchups += 1;
 
         Some(matchup)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.total_matchups.saturating_sub(self.yielded_matchups);
+        match usize::try_from(remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+impl FusedIterator for SubnetworkMatchupIterator {}
+
+/// Order in which the manager schedules the matchups of the network.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchupOrdering {
+    /// Subnetwork by subnetwork, then inter-subnetwork and remainder matchups.
+    #[default]
+    IntraFirst,
+    /// Inter-subnetwork and remainder matchups, then subnetwork by subnetwork.
+    InterFirst,
+    /// One matchup from each subnetwork in turn,
+    /// with the inter-subnetwork matchups as the last lane of every round.
+    Interleaved,
+}
+
+impl std::str::FromStr for MatchupOrdering {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "intra_first" => Ok(Self::IntraFirst),
+            "inter_first" => Ok(Self::InterFirst),
+            "interleaved" => Ok(Self::Interleaved),
+            _ => Err(format!("Unknown matchup ordering: {name}")),
+        }
+    }
+}
+
+/// Serializable position of a manager, from which the schedule resumes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManagerCursor {
+    pub network_size: InputInt,
+    pub base: BaseInt,
+    pub ordering: MatchupOrdering,
+    pub seed: Option<u64>,
+    /// Subnetworks still scheduled, in their scheduling order.
+    pub subnetworks: Vec<SubnetworkCursor>,
+    /// Inter-subnetwork and remainder matchups yielded so far.
+    pub intersubnetwork_position: usize,
+    pub interleaving_lane: usize,
 }
 
 /// Iterator for generating the matchups of the whole network.
-/// Generates intra-subnetwork matchups first,
+/// Every consumer gets the same schedule for a given ordering.
+/// By default, generates intra-subnetwork matchups first,
 /// then inter-subnetwork matchups,
 /// then remainder matchups.
 #[derive(Default, Debug)]
 pub struct LocalMatchupsManager {
     pub network_gber: gber::Decomposition,
     pub subnetwork_iterators: VecDeque<SubnetworkMatchupIterator>,
+    /// Every inter-subnetwork and remainder matchup, yielded or not.
     pub intersubnetwork_matchups: VecDeque<Vec<InputInt>>,
+    pub ordering: MatchupOrdering,
+    /// Maps network positions to items; without it, positions are the items.
+    pub permutation: Option<SeededPermutation>,
+    network_size: InputInt,
+    /// Untouched copies of the subnetwork iterators, for random access.
+    scheduled_subnetworks: Vec<SubnetworkMatchupIterator>,
+    interleaving_lane: usize,
+    yielded_intersubnetwork_matchups: usize,
 }
 
 impl LocalMatchupsManager {
-    pub fn new(network_gber: gber::Decomposition) -> Self {
+    pub fn new(network_gber: gber::Decomposition) -> Result<Self, SnicError> {
+        let network_size = network_gber.to_decimal()?;
+        if network_size == 0 {
+            return Err(SnicError::EmptyNetwork);
+        }
+        if network_gber.component_powers.is_empty() {
+            return Err(SnicError::NetworkSmallerThanMatchup {
+                network_size,
+                matchup_size: network_gber.base,
+            });
+        }
         let mut rolling_offset: InputInt = 0;
-        let subnetwork_iterators = network_gber
+        let scheduled_subnetworks: Vec<SubnetworkMatchupIterator> = network_gber
             .stream_all_components()
             .map(|sn_size| {
-                let iter =
-                    SubnetworkMatchupIterator::new(sn_size, network_gber.base, rolling_offset).unwrap();
-                rolling_offset += sn_size;
-                iter
+                let iter = SubnetworkMatchupIterator::new(sn_size, network_gber.base, rolling_offset)?;
+                rolling_offset = rolling_offset.checked_add(sn_size).ok_or(SnicError::Overflow)?;
+                Ok(iter)
             })
-            .collect();
-        let intersubnetwork_matchups = create_intersubnetwork_matchups(&network_gber);
-        Self {
+            .collect::<Result<_, SnicError>>()?;
+        let intersubnetwork_matchups = create_intersubnetwork_matchups(&network_gber)?;
+        // Positions within the schedule are counted in u64 from here on
+        scheduled_subnetworks
+            .iter()
+            .map(SubnetworkMatchupIterator::total_matchups)
+            .try_fold(intersubnetwork_matchups.len() as u64, u64::checked_add)
+            .ok_or(SnicError::Overflow)?;
+        Ok(Self {
             network_gber,
-            subnetwork_iterators,
+            subnetwork_iterators: scheduled_subnetworks.iter().cloned().collect(),
             intersubnetwork_matchups,
+            network_size,
+            scheduled_subnetworks,
+            ..Default::default()
+        })
+    }
+
+    pub fn with_ordering(mut self, ordering: MatchupOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Shuffle the items over the network positions, reproducibly from the seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.permutation = Some(SeededPermutation::new(self.network_size, seed));
+        self
+    }
+
+    /// Resume right after the matchups already yielded at the cursor.
+    pub fn resume(cursor: &ManagerCursor) -> Result<Self, SnicError> {
+        let mut manager = Self::new(gber::Decomposition::new(cursor.network_size, cursor.base)?)?
+            .with_ordering(cursor.ordering);
+        if let Some(seed) = cursor.seed {
+            manager = manager.with_seed(seed);
+        }
+        // Exhausted subnetworks drop out of the schedule, the rest keep their order
+        let mut scheduled = manager.subnetwork_iterators.iter();
+        for subnetwork in &cursor.subnetworks {
+            let matches_schedule = scheduled.any(|iterator| {
+                (iterator.network_size, iterator.matchup_size, iterator.offset)
+                    == (subnetwork.network_size, subnetwork.matchup_size, subnetwork.offset)
+            });
+            if !matches_schedule {
+                return Err(SnicError::InvalidParameter(format!(
+                    "the cursor subnetwork at offset {} is not part of the network",
+                    subnetwork.offset
+                )));
+            }
+        }
+        if cursor.intersubnetwork_position > manager.intersubnetwork_matchups.len() {
+            return Err(SnicError::InvalidParameter(format!(
+                "cursor position {} is past the {} inter-subnetwork matchups",
+                cursor.intersubnetwork_position,
+                manager.intersubnetwork_matchups.len()
+            )));
+        }
+        if cursor.interleaving_lane > cursor.subnetworks.len() {
+            return Err(SnicError::InvalidParameter(format!(
+                "interleaving lane {} is past the {} scheduled subnetworks",
+                cursor.interleaving_lane,
+                cursor.subnetworks.len()
+            )));
+        }
+        manager.subnetwork_iterators = cursor
+            .subnetworks
+            .iter()
+            .map(SubnetworkMatchupIterator::resume)
+            .collect::<Result<_, SnicError>>()?;
+        manager.yielded_intersubnetwork_matchups = cursor.intersubnetwork_position;
+        manager.interleaving_lane = cursor.interleaving_lane;
+        Ok(manager)
+    }
+
+    pub fn network_size(&self) -> InputInt {
+        self.network_size
+    }
+
+    /// Matchups of the whole plan, however many were yielded.
+    pub fn total_matchups(&self) -> u64 {
+        self.lane_lengths().map(|(_, length)| length).sum()
+    }
+
+    pub fn cursor(&self) -> ManagerCursor {
+        ManagerCursor {
+            network_size: self.network_size,
+            base: self.network_gber.base,
+            ordering: self.ordering,
+            seed: self.permutation.as_ref().map(SeededPermutation::seed),
+            subnetworks: self
+                .subnetwork_iterators
+                .iter()
+                .map(SubnetworkMatchupIterator::cursor)
+                .collect(),
+            intersubnetwork_position: self.yielded_intersubnetwork_matchups,
+            interleaving_lane: self.interleaving_lane,
         }
     }
+
+    /// Matchup at a position counted from the start of the schedule,
+    /// regardless of how far the iteration went.
+    pub fn matchup_at(&self, position: u64) -> Option<Vec<InputInt>> {
+        let (lane, lane_position) = self.locate(position)?;
+        let matchup = match lane {
+            Some(subnetwork) => self.scheduled_subnetworks[subnetwork].matchup_at(lane_position)?,
+            None => self.intersubnetwork_matchups[lane_position as usize].clone(),
+        };
+        Some(place_items(self.permutation.as_ref(), matchup))
+    }
+
+    /// Schedule positions of the matchups holding an item, ascending.
+    pub fn matchups_containing(&self, item: InputInt) -> Vec<u64> {
+        if item >= self.network_size {
+            return vec![];
+        }
+        let position = match &self.permutation {
+            Some(permutation) => permutation.position_of(item),
+            None => item,
+        };
+        let mut schedule_positions = vec![];
+        for (subnetwork, iterator) in self.scheduled_subnetworks.iter().enumerate() {
+            schedule_positions.extend(
+                iterator
+                    .matchups_containing(position)
+                    .into_iter()
+                    .map(|lane_position| self.schedule_position_of(Some(subnetwork), lane_position)),
+            );
+        }
+        for (lane_position, matchup) in self.intersubnetwork_matchups.iter().enumerate() {
+            if matchup.contains(&position) {
+                schedule_positions.push(self.schedule_position_of(None, lane_position as u64));
+            }
+        }
+        schedule_positions.sort_unstable();
+        schedule_positions
+    }
+
+    /// Matchups per lane: the subnetworks, then the inter-subnetwork lane.
+    fn lane_lengths(&self) -> impl Iterator<Item = (Option<usize>, u64)> + '_ {
+        self.scheduled_subnetworks
+            .iter()
+            .enumerate()
+            .map(|(subnetwork, iterator)| {
+                (Some(subnetwork), iterator.total_matchups)
+            })
+            .chain(std::iter::once((None, self.intersubnetwork_matchups.len() as u64)))
+    }
+
+    /// Matchups scheduled before a round of the interleaving.
+    fn matchups_before_round(&self, round: u64) -> u64 {
+        self.lane_lengths().map(|(_, length)| length.min(round)).sum()
+    }
+
+    /// Lane and position within the lane of a schedule position.
+    fn locate(&self, position: u64) -> Option<(Option<usize>, u64)> {
+        if self.ordering == MatchupOrdering::Interleaved {
+            let longest_lane = self.lane_lengths().map(|(_, length)| length).max()?;
+            if position >= self.matchups_before_round(longest_lane) {
+                return None;
+            }
+            // The last round starting at or before the position
+            let (mut low, mut high) = (0, longest_lane);
+            while high - low > 1 {
+                let middle = low + (high - low) / 2;
+                if self.matchups_before_round(middle) <= position {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            let round_position = (position - self.matchups_before_round(low)) as usize;
+            let (lane, _) = self
+                .lane_lengths()
+                .filter(|&(_, length)| length > low)
+                .nth(round_position)?;
+            return Some((lane, low));
+        }
+        let mut remaining = position;
+        for (lane, length) in self.ordered_lane_lengths() {
+            if remaining < length {
+                return Some((lane, remaining));
+            }
+            remaining -= length;
+        }
+        None
+    }
+
+    fn schedule_position_of(&self, lane: Option<usize>, lane_position: u64) -> u64 {
+        if self.ordering == MatchupOrdering::Interleaved {
+            let earlier_lanes = self
+                .lane_lengths()
+                .take_while(|&(other_lane, _)| other_lane != lane)
+                .filter(|&(_, length)| length > lane_position)
+                .count() as u64;
+            return self.matchups_before_round(lane_position) + earlier_lanes;
+        }
+        self.ordered_lane_lengths()
+            .into_iter()
+            .take_while(|&(other_lane, _)| other_lane != lane)
+            .map(|(_, length)| length)
+            .sum::<u64>()
+            + lane_position
+    }
+
+    /// Lanes in the order the sequential orderings exhaust them.
+    fn ordered_lane_lengths(&self) -> Vec<(Option<usize>, u64)> {
+        let mut lanes = self.lane_lengths().collect::<Vec<_>>();
+        if self.ordering == MatchupOrdering::InterFirst {
+            lanes.rotate_right(1);
+        }
+        lanes
+    }
+
+    fn next_intersubnetwork_matchup(&mut self) -> Option<Vec<InputInt>> {
+        let matchup = self
+            .intersubnetwork_matchups
+            .get(self.yielded_intersubnetwork_matchups)?
+            .clone();
+        self.yielded_intersubnetwork_matchups += 1;
+        Some(matchup)
+    }
+
+    fn next_intrasubnetwork_matchup(&mut self) -> Option<Vec<InputInt>> {
+        while let Some(subnetwork_iterator) = self.subnetwork_iterators.front_mut() {
+            if let Some(matchup) = subnetwork_iterator.next() {
+                return Some(matchup);
+            }
+            self.subnetwork_iterators.pop_front();
+        }
+        None
+    }
+
+    fn next_interleaved_matchup(&mut self) -> Option<Vec<InputInt>> {
+        loop {
+            let lane = self.interleaving_lane;
+            if lane < self.subnetwork_iterators.len() {
+                if let Some(matchup) = self.subnetwork_iterators[lane].next() {
+                    self.interleaving_lane += 1;
+                    return Some(matchup);
+                }
+                // The lane is exhausted, so the next one slides into its place
+                self.subnetwork_iterators.remove(lane);
+                continue;
+            }
+            self.interleaving_lane = 0;
+            if let Some(matchup) = self.next_intersubnetwork_matchup() {
+                return Some(matchup);
+            }
+            if self.subnetwork_iterators.is_empty() {
+                return None;
+            }
+        }
+    }
+}
+
+impl Iterator for LocalMatchupsManager {
+    type Item = Vec<InputInt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let matchup = match self.ordering {
+            MatchupOrdering::IntraFirst => self
+                .next_intrasubnetwork_matchup()
+                .or_else(|| self.next_intersubnetwork_matchup()),
+            MatchupOrdering::InterFirst => self
+                .next_intersubnetwork_matchup()
+                .or_else(|| self.next_intrasubnetwork_matchup()),
+            MatchupOrdering::Interleaved => self.next_interleaved_matchup(),
+        }?;
+        Some(place_items(self.permutation.as_ref(), matchup))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self
+            .subnetwork_iterators
+            .iter()
+            .map(|iter| iter.size_hint().0)
+            .sum::<usize>()
+            + (self.intersubnetwork_matchups.len() - self.yielded_intersubnetwork_matchups);
+        (remaining, Some(remaining))
+    }
+}
+
+impl FusedIterator for LocalMatchupsManager {}
+
+/// Contiguous part of the plan, for one of several independent workers.
+/// Shards cut the plan lane by lane, the subnetworks then the inter-subnetwork lane,
+/// so a shard spans few subnetworks; interleaving does not apply within a shard.
+/// Shard sizes differ by at most one matchup and together they cover the plan once.
+#[derive(Debug, Clone)]
+pub struct MatchupShard {
+    pub shard_index: usize,
+    pub shard_count: usize,
+    slices: VecDeque<ShardSlice>,
+    permutation: Option<SeededPermutation>,
+}
+
+#[derive(Debug, Clone)]
+enum ShardSlice {
+    Subnetwork {
+        iterator: SubnetworkMatchupIterator,
+        remaining: u64,
+    },
+    Intersubnetwork(VecDeque<Vec<InputInt>>),
+}
+
+impl LocalMatchupsManager {
+    /// Matchups of one shard of the whole plan, regardless of how far the iteration went.
+    pub fn shard(&self, shard_index: usize, shard_count: usize) -> Result<MatchupShard, SnicError> {
+        if shard_index >= shard_count {
+            return Err(SnicError::InvalidParameter(format!(
+                "shard {shard_index} is not one of {shard_count} shards"
+            )));
+        }
+        let lanes = self.ordered_lane_lengths();
+        let total = lanes.iter().map(|(_, length)| length).sum::<u64>();
+        let boundary = |shard| (total as u128 * shard as u128 / shard_count as u128) as u64;
+        let (start, end) = (boundary(shard_index), boundary(shard_index + 1));
+        let mut slices = VecDeque::new();
+        let mut lane_start = 0;
+        for (lane, length) in lanes {
+            let lane_end = lane_start + length;
+            let (slice_start, slice_end) = (start.max(lane_start), end.min(lane_end));
+            if slice_start < slice_end {
+                let (first, last) = (slice_start - lane_start, slice_end - lane_start);
+                slices.push_back(match lane {
+                    Some(subnetwork) => {
+                        let mut cursor = self.scheduled_subnetworks[subnetwork].cursor();
+                        cursor.position = first;
+                        ShardSlice::Subnetwork {
+                            iterator: SubnetworkMatchupIterator::resume(&cursor)?,
+                            remaining: last - first,
+                        }
+                    }
+                    None => ShardSlice::Intersubnetwork(
+                        self.intersubnetwork_matchups
+                            .range(first as usize..last as usize)
+                            .cloned()
+                            .collect(),
+                    ),
+                });
+            }
+            lane_start = lane_end;
+        }
+        Ok(MatchupShard {
+            shard_index,
+            shard_count,
+            slices,
+            permutation: self.permutation.clone(),
+        })
+    }
+}
+
+impl Iterator for MatchupShard {
+    type Item = Vec<InputInt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let matchup = loop {
+            match self.slices.front_mut()? {
+                ShardSlice::Subnetwork {
+                    iterator,
+                    remaining,
+                } if *remaining > 0 => {
+                    *remaining -= 1;
+                    break iterator.next()?;
+                }
+                ShardSlice::Intersubnetwork(matchups) if !matchups.is_empty() => {
+                    break matchups.pop_front()?;
+                }
+                _ => {
+                    self.slices.pop_front();
+                }
+            }
+        };
+        Some(place_items(self.permutation.as_ref(), matchup))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self
+            .slices
+            .iter()
+            .map(|slice| match slice {
+                ShardSlice::Subnetwork { remaining, .. } => *remaining as usize,
+                ShardSlice::Intersubnetwork(matchups) => matchups.len(),
+            })
+            .sum();
+        (remaining, Some(remaining))
+    }
 }
 
-fn create_intersubnetwork_matchups(decomposition: &gber::Decomposition) -> VecDeque<Vec<InputInt>> {
+/// Swap network positions for the items placed there.
+fn place_items(permutation: Option<&SeededPermutation>, matchup: Vec<InputInt>) -> Vec<InputInt> {
+    match permutation {
+        Some(permutation) => matchup
+            .into_iter()
+            .map(|position| permutation.item_at(position))
+            .collect(),
+        None => matchup,
+    }
+}
+
+fn create_intersubnetwork_matchups(
+    decomposition: &gber::Decomposition,
+) -> Result<VecDeque<Vec<InputInt>>, SnicError> {
     let component_pairs: Vec<(InputInt, u8)> = zip(
         decomposition.stream_all_components(),
         decomposition.component_powers.clone(),
     )
     .collect();
-    let seat_allocations = matchup_allocations_for(&component_pairs, decomposition.base);
+    let seat_allocations = matchup_allocations_for(&component_pairs, decomposition.base)?;
     let mut component_iterator = component_pairs.iter();
 
     let mut total_hub_seats: BaseInt = seat_allocations
@@ -165,34 +764,34 @@ fn create_intersubnetwork_matchups(decomposition: &gber::Decomposition) -> VecDe
         total_hub_seats += decomposition.base - decomposition.remainder;
     }
     if total_hub_seats == 0 {
-        return VecDeque::new();
+        return Ok(VecDeque::new());
     }
 
     let mut index_offset: InputInt = 0;
     let mut hub_seat_offset: BaseInt = 0;
-    let (hub_network_size, _) = component_iterator.next().unwrap();
+    let (hub_network_size, _) = component_iterator.next().ok_or(SnicError::EmptyNetwork)?;
     let reserved_hub_seats =
-        take_elements_uniformly(*hub_network_size, total_hub_seats, index_offset)
+        take_elements_uniformly(*hub_network_size, total_hub_seats, index_offset)?
             .collect::<Vec<InputInt>>();
     index_offset += hub_network_size;
 
-    let mut inter_matchups: VecDeque<Vec<InputInt>> = zip(component_iterator, seat_allocations)
+    let mut inter_matchups = zip(component_iterator, seat_allocations)
         .map(|((spoke_size, _), (hub_seats, spoke_seats))| {
             let reserved_spoke_seats_stream =
-                take_elements_uniformly(*spoke_size, spoke_seats, index_offset);
+                take_elements_uniformly(*spoke_size, spoke_seats, index_offset)?;
             let current_hub_seats =
                 get_vector_slice_from(&reserved_hub_seats, hub_seat_offset, hub_seats);
             index_offset += spoke_size;
             hub_seat_offset += hub_seats;
-            current_hub_seats
+            Ok(current_hub_seats
                 .into_iter()
                 .chain(reserved_spoke_seats_stream)
-                .collect::<Vec<InputInt>>()
+                .collect::<Vec<InputInt>>())
         })
-        .collect();
+        .collect::<Result<VecDeque<Vec<InputInt>>, SnicError>>()?;
 
     if decomposition.remainder == 0 {
-        return inter_matchups;
+        return Ok(inter_matchups);
     }
     let remainder_hub_seats = get_vector_slice_from(
         &reserved_hub_seats,
@@ -202,7 +801,7 @@ fn create_intersubnetwork_matchups(decomposition: &gber::Decomposition) -> VecDe
     let remainder_elements = index_offset..(index_offset + decomposition.remainder as InputInt);
     let remainder_matchup = remainder_elements.chain(remainder_hub_seats).collect();
     inter_matchups.push_back(remainder_matchup);
-    inter_matchups
+    Ok(inter_matchups)
 }
 
 fn get_vector_slice_from<T: Clone>(view: &[T], start: BaseInt, quantity: BaseInt) -> Vec<T> {
@@ -227,36 +826,39 @@ fn allocate_matchup_seats_for(
 fn matchup_allocations_for(
     network_size_components: &[(InputInt, u8)],
     matchup_size: BaseInt,
-) -> Vec<(BaseInt, BaseInt)> {
+) -> Result<Vec<(BaseInt, BaseInt)>, SnicError> {
     let mut termiter = network_size_components.iter().filter(|comp| comp.1 > 0);
     // First term is the hub
-    let hub_exponent = termiter.next().unwrap().1;
-    termiter
+    let hub_exponent = termiter.next().ok_or(SnicError::EmptyNetwork)?.1;
+    Ok(termiter
         .map(move |comp| allocate_matchup_seats_for(hub_exponent, comp.1, matchup_size))
-        .collect()
+        .collect())
 }
 
+/// Spread the requested quantity of elements evenly over the network.
+/// Wraps around when more elements are requested than the network holds,
+/// so a small hub can still serve every spoke.
 fn take_elements_uniformly(
     network_size: InputInt,
     quantity: BaseInt,
     offset: InputInt,
-) -> impl Iterator<Item = InputInt> {
-    let window_size: InputInt = network_size
-        .checked_div(quantity as InputInt)
-        .ok_or("Division by zero")
-        .expect("Cannot take 0 elements");
-    (0..network_size)
-        .filter(move |ix| ix % window_size == 0)
-        .map(move |ix| ix + offset)
-        .take(quantity.into())
+) -> Result<impl Iterator<Item = InputInt>, SnicError> {
+    if network_size == 0 {
+        return Err(SnicError::EmptyNetwork);
+    }
+    let window_size: InputInt = (network_size / quantity.max(1) as InputInt).max(1);
+    Ok((0..quantity as InputInt)
+        .map(move |seat| (seat * window_size) % network_size + offset))
 }
 
 #[cfg(test)]
 mod test_match_count {
     use crate::gber::Decomposition;
     use super::calculate_matchups_number_for;
-    use super::LocalMatchupsManager;
+    use super::{LocalMatchupsManager, SubnetworkMatchupIterator};
     use crate::common_types::{BaseInt, InputInt};
+    #[cfg(feature = "u64-indices")]
+    use crate::error::SnicError;
     use std::collections::HashSet;
     const NETWORK_SIZE: InputInt = BaseInt::MAX as InputInt;
     const MATCH_SIZE: BaseInt = 2;
@@ -265,7 +867,7 @@ mod test_match_count {
     fn matches_are_unique() {
         let decomposition =
             Decomposition::new(NETWORK_SIZE, MATCH_SIZE).expect("Invalid decomposition values");
-        let network = LocalMatchupsManager::new(decomposition);
+        let network = LocalMatchupsManager::new(decomposition).unwrap();
         let matchups = network.subnetwork_iterators
             .into_iter().flatten().collect::<Vec<Vec<InputInt>>>();
         let matchups_length = matchups.len();
@@ -279,14 +881,444 @@ mod test_match_count {
         let decomposition =
             Decomposition::new(NETWORK_SIZE, MATCH_SIZE).expect("Invalid decomposition values");
         let math_calculation = decomposition.stream_all_components()
-            .map(|comp_size| calculate_matchups_number_for(comp_size, MATCH_SIZE))
+            .map(|comp_size| calculate_matchups_number_for(comp_size, MATCH_SIZE).unwrap())
             .sum::<u64>();
-        let network = LocalMatchupsManager::new(decomposition);
+        let network = LocalMatchupsManager::new(decomposition).unwrap();
         let counted_subnetwork_matchups = network.subnetwork_iterators
             .into_iter().flatten().map(|_| 1u64).sum::<u64>();
         assert_eq!(math_calculation, counted_subnetwork_matchups);
     }
 
+    #[test]
+    fn walk_sections_level_by_level() {
+        // A power of the base is derived from positions, other sizes walk their sections
+        let power = SubnetworkMatchupIterator::new(9, 3, 0).unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            power,
+            vec![vec![0, 3, 6], vec![1, 4, 7], vec![2, 5, 8], vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8]]
+        );
+        let other = SubnetworkMatchupIterator::new(12, 3, 0).unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            other,
+            vec![
+                vec![0, 4, 8], vec![1, 5, 9], vec![2, 6, 10], vec![3, 7, 11],
+                vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8], vec![9, 10, 11],
+            ]
+        );
+    }
+
+    #[test]
+    fn stay_exhausted() {
+        for network_size in [9, 12] {
+            let mut iterator = SubnetworkMatchupIterator::new(network_size, 3, 0).unwrap();
+            let total = iterator.by_ref().count();
+            assert_eq!(total as u64, iterator.total_matchups());
+            assert!((0..1_000).all(|_| iterator.next().is_none()), "{network_size}");
+            assert_eq!(iterator.size_hint(), (0, Some(0)));
+        }
+    }
+
+    #[cfg(feature = "u64-indices")]
+    #[test]
+    fn reject_plans_too_large_to_count() {
+        let network_size: InputInt = 1 << 63;
+        assert_eq!(calculate_matchups_number_for(network_size, 2), Err(SnicError::Overflow));
+        assert_eq!(
+            SubnetworkMatchupIterator::new(network_size, 2, 0).unwrap_err(),
+            SnicError::Overflow
+        );
+        let decomposition = Decomposition::new(network_size, 2).unwrap();
+        assert_eq!(LocalMatchupsManager::new(decomposition).unwrap_err(), SnicError::Overflow);
+    }
+
+    #[test]
+    fn formula_matches_iteration_on_powers() {
+        for base in [2, 3, 5, 10] {
+            let mut subnetwork_size = base as InputInt;
+            while subnetwork_size <= 100_000 {
+                let counted = SubnetworkMatchupIterator::new(subnetwork_size, base, 0)
+                    .unwrap().count() as u64;
+                assert_eq!(calculate_matchups_number_for(subnetwork_size, base), Ok(counted));
+                subnetwork_size *= base as InputInt;
+            }
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod test_network_validation {
+    use crate::gber::Decomposition;
+    use super::{LocalMatchupsManager, SubnetworkMatchupIterator};
+    use crate::common_types::{BaseInt, InputInt};
+    use crate::error::SnicError;
+    use std::collections::HashSet;
+
+    #[test]
+    fn reject_empty_network() {
+        let decomposition = Decomposition::new(0, 3).unwrap();
+        assert_eq!(LocalMatchupsManager::new(decomposition).unwrap_err(), SnicError::EmptyNetwork);
+        assert_eq!(SubnetworkMatchupIterator::new(0, 3, 0).unwrap_err(), SnicError::EmptyNetwork);
+    }
+
+    #[test]
+    fn reject_network_smaller_than_matchup() {
+        let expected = SnicError::NetworkSmallerThanMatchup {
+            network_size: 4,
+            matchup_size: 5,
+        };
+        let decomposition = Decomposition::new(4, 5).unwrap();
+        assert_eq!(LocalMatchupsManager::new(decomposition).unwrap_err(), expected);
+        assert_eq!(SubnetworkMatchupIterator::new(4, 5, 0).unwrap_err(), expected);
+    }
+
+    #[test]
+    fn small_hubs_serve_all_spokes() {
+        for base in 2..=12 as BaseInt {
+            for network_size in base as InputInt..=300 {
+                let decomposition = Decomposition::new(network_size, base).unwrap();
+                let network = LocalMatchupsManager::new(decomposition).unwrap();
+                for matchup in network {
+                    assert_eq!(matchup.len(), base as usize);
+                    assert_eq!(matchup.iter().collect::<HashSet<_>>().len(), base as usize);
+                    assert!(matchup.iter().all(|&item| item < network_size));
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test_matchup_ordering {
+    use crate::gber::Decomposition;
+    use super::{LocalMatchupsManager, MatchupOrdering};
+    use crate::common_types::{BaseInt, InputInt};
+    use std::collections::HashSet;
+    const NETWORK_SIZE: InputInt = 1003;
+    const MATCH_SIZE: BaseInt = 5;
+    const ORDERINGS: [MatchupOrdering; 3] = [
+        MatchupOrdering::IntraFirst,
+        MatchupOrdering::InterFirst,
+        MatchupOrdering::Interleaved,
+    ];
+
+    fn collect_with(ordering: MatchupOrdering) -> Vec<Vec<InputInt>> {
+        let decomposition =
+            Decomposition::new(NETWORK_SIZE, MATCH_SIZE).expect("Invalid decomposition values");
+        LocalMatchupsManager::new(decomposition).unwrap().with_ordering(ordering).collect()
+    }
+
+    #[test]
+    fn orderings_share_matchups() {
+        let reference = collect_with(MatchupOrdering::IntraFirst)
+            .into_iter().collect::<HashSet<Vec<InputInt>>>();
+        for ordering in ORDERINGS {
+            let matchups = collect_with(ordering);
+            assert_eq!(matchups.len(), reference.len());
+            assert_eq!(matchups.into_iter().collect::<HashSet<Vec<InputInt>>>(), reference);
+        }
+    }
+
+    #[test]
+    fn size_hint_is_exact() {
+        for ordering in ORDERINGS {
+            let decomposition =
+                Decomposition::new(NETWORK_SIZE, MATCH_SIZE).expect("Invalid decomposition values");
+            let mut network = LocalMatchupsManager::new(decomposition).unwrap().with_ordering(ordering);
+            let mut remaining = network.size_hint().0;
+            while network.next().is_some() {
+                remaining -= 1;
+                assert_eq!(network.size_hint(), (remaining, Some(remaining)));
+            }
+            assert_eq!(remaining, 0);
+        }
+    }
+
+    #[test]
+    fn intersubnetwork_placement() {
+        let decomposition =
+            Decomposition::new(NETWORK_SIZE, MATCH_SIZE).expect("Invalid decomposition values");
+        let inter_matchups =
+            Vec::from(LocalMatchupsManager::new(decomposition).unwrap().intersubnetwork_matchups);
+        let intra_first = collect_with(MatchupOrdering::IntraFirst);
+        let inter_first = collect_with(MatchupOrdering::InterFirst);
+        assert_eq!(intra_first[intra_first.len() - inter_matchups.len()..], inter_matchups);
+        assert_eq!(inter_first[..inter_matchups.len()], inter_matchups);
+    }
+
+    #[test]
+    fn interleaving_alternates_subnetworks() {
+        let decomposition =
+            Decomposition::new(NETWORK_SIZE, MATCH_SIZE).expect("Invalid decomposition values");
+        let hub_size = decomposition.stream_all_components().next().unwrap();
+        let interleaved = collect_with(MatchupOrdering::Interleaved);
+        assert!(interleaved[0].iter().all(|&item| item < hub_size));
+        assert!(interleaved[1].iter().all(|&item| item >= hub_size));
+    }
+}
+
+#[cfg(test)]
+mod test_seeded_placement {
+    use crate::gber::Decomposition;
+    use super::LocalMatchupsManager;
+    use crate::common_types::{BaseInt, InputInt};
+    const NETWORK_SIZE: InputInt = 1003;
+    const MATCH_SIZE: BaseInt = 5;
+
+    fn manager() -> LocalMatchupsManager {
+        let decomposition =
+            Decomposition::new(NETWORK_SIZE, MATCH_SIZE).expect("Invalid decomposition values");
+        LocalMatchupsManager::new(decomposition).unwrap()
+    }
+
+    #[test]
+    fn replay_from_seed() {
+        let shuffled = manager().with_seed(11).collect::<Vec<Vec<InputInt>>>();
+        assert_eq!(shuffled, manager().with_seed(11).collect::<Vec<Vec<InputInt>>>());
+        assert_ne!(shuffled, manager().with_seed(12).collect::<Vec<Vec<InputInt>>>());
+    }
+
+    #[test]
+    fn permute_unshuffled_structure() {
+        let shuffled = manager().with_seed(11);
+        let permutation = shuffled.permutation.clone().unwrap();
+        for (plain, shuffled) in manager().zip(shuffled) {
+            let expected = plain
+                .into_iter()
+                .map(|position| permutation.item_at(position))
+                .collect::<Vec<InputInt>>();
+            assert_eq!(shuffled, expected);
+        }
+    }
+
+    #[test]
+    fn spread_hub_over_items() {
+        let hub_size = Decomposition::new(NETWORK_SIZE, MATCH_SIZE)
+            .unwrap()
+            .stream_all_components()
+            .next()
+            .unwrap();
+        let first_matchup = manager().with_seed(11).next().unwrap();
+        assert!(first_matchup.iter().any(|&item| item >= hub_size));
+    }
+}
+
+
+#[cfg(test)]
+mod test_resumable_cursors {
+    use crate::gber::Decomposition;
+    use super::{
+        LocalMatchupsManager, ManagerCursor, MatchupOrdering, SubnetworkCursor,
+        SubnetworkMatchupIterator,
+    };
+    use crate::error::SnicError;
+
+    #[test]
+    fn subnetworks_resume_at_every_position() {
+        for (network_size, base, offset) in [(64, 2, 0), (81, 3, 7), (125, 5, 100), (4, 4, 1)] {
+            let start = || SubnetworkMatchupIterator::new(network_size, base, offset).unwrap();
+            let mut live = start();
+            for position in 0..=live.size_hint().0 {
+                let resumed = SubnetworkMatchupIterator::resume(&live.cursor()).unwrap();
+                assert_eq!(resumed.size_hint(), live.size_hint());
+                assert!(resumed.eq(start().skip(position)), "{network_size}, position {position}");
+                live.next();
+            }
+        }
+    }
+
+    #[test]
+    fn reject_invalid_subnetwork_cursors() {
+        let past_end = SubnetworkCursor {
+            network_size: 27,
+            matchup_size: 3,
+            offset: 0,
+            position: 28,
+        };
+        let not_a_power = SubnetworkCursor {
+            network_size: 28,
+            position: 0,
+            ..past_end
+        };
+        for cursor in [past_end, not_a_power] {
+            assert!(matches!(
+                SubnetworkMatchupIterator::resume(&cursor),
+                Err(SnicError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn managers_resume_through_serialization() {
+        for ordering in [
+            MatchupOrdering::IntraFirst,
+            MatchupOrdering::InterFirst,
+            MatchupOrdering::Interleaved,
+        ] {
+            for seed in [None, Some(3)] {
+                let start = || {
+                    let decomposition = Decomposition::new(1003, 5).unwrap();
+                    let manager = LocalMatchupsManager::new(decomposition)
+                        .unwrap()
+                        .with_ordering(ordering);
+                    match seed {
+                        Some(seed) => manager.with_seed(seed),
+                        None => manager,
+                    }
+                };
+                let total = start().count();
+                for position in [0, 1, 7, 150, total - 3, total] {
+                    let mut live = start();
+                    live.by_ref().take(position).for_each(drop);
+                    let checkpoint = serde_json::to_string(&live.cursor()).unwrap();
+                    let cursor = serde_json::from_str::<ManagerCursor>(&checkpoint).unwrap();
+                    let resumed = LocalMatchupsManager::resume(&cursor).unwrap();
+                    assert_eq!(resumed.size_hint(), live.size_hint());
+                    assert!(resumed.eq(live), "{ordering:?}, seed {seed:?}, position {position}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reject_foreign_manager_cursors() {
+        let mut cursor = LocalMatchupsManager::new(Decomposition::new(1003, 5).unwrap())
+            .unwrap()
+            .cursor();
+        cursor.subnetworks[0].offset += 1;
+        assert!(matches!(
+            LocalMatchupsManager::resume(&cursor),
+            Err(SnicError::InvalidParameter(_))
+        ));
+    }
+}
+
+
+#[cfg(test)]
+mod test_random_access {
+    use crate::gber::Decomposition;
+    use super::{LocalMatchupsManager, MatchupOrdering, SubnetworkMatchupIterator};
+    use crate::common_types::{BaseInt, InputInt};
+
+    fn positions_by_item(schedule: &[Vec<InputInt>], network_size: InputInt) -> Vec<Vec<u64>> {
+        let mut positions = vec![vec![]; network_size as usize];
+        for (position, matchup) in schedule.iter().enumerate() {
+            for &item in matchup {
+                positions[item as usize].push(position as u64);
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn subnetworks_match_iteration() {
+        for (network_size, base, offset) in [(64, 2, 0), (81, 3, 7), (125, 5, 100), (4, 4, 1)] {
+            let iterator = SubnetworkMatchupIterator::new(network_size, base, offset).unwrap();
+            let schedule = iterator.clone().collect::<Vec<Vec<InputInt>>>();
+            for (position, matchup) in schedule.iter().enumerate() {
+                assert_eq!(iterator.matchup_at(position as u64).as_ref(), Some(matchup));
+            }
+            assert_eq!(iterator.matchup_at(schedule.len() as u64), None);
+            let shifted = schedule
+                .iter()
+                .map(|matchup| matchup.iter().map(|item| item - offset).collect())
+                .collect::<Vec<Vec<InputInt>>>();
+            for (local_item, positions) in
+                positions_by_item(&shifted, network_size).into_iter().enumerate()
+            {
+                assert_eq!(iterator.matchups_containing(local_item as InputInt + offset), positions);
+            }
+            assert!(iterator.matchups_containing(offset + network_size).is_empty());
+        }
+    }
+
+    #[test]
+    fn managers_match_iteration() {
+        let cases: [(InputInt, BaseInt); 5] = [(1003, 5), (100, 3), (21, 10), (7, 2), (2, 2)];
+        for (network_size, base) in cases {
+            for ordering in [
+                MatchupOrdering::IntraFirst,
+                MatchupOrdering::InterFirst,
+                MatchupOrdering::Interleaved,
+            ] {
+                for seed in [None, Some(5)] {
+                    let decomposition = Decomposition::new(network_size, base).unwrap();
+                    let mut manager =
+                        LocalMatchupsManager::new(decomposition).unwrap().with_ordering(ordering);
+                    if let Some(seed) = seed {
+                        manager = manager.with_seed(seed);
+                    }
+                    let schedule = manager.by_ref().take(3).collect::<Vec<Vec<InputInt>>>();
+                    let schedule = [schedule, manager.by_ref().collect()].concat();
+                    for (position, matchup) in schedule.iter().enumerate() {
+                        assert_eq!(
+                            manager.matchup_at(position as u64).as_ref(),
+                            Some(matchup),
+                            "{network_size}/{base}, {ordering:?}, position {position}"
+                        );
+                    }
+                    assert_eq!(manager.matchup_at(schedule.len() as u64), None);
+                    for (item, positions) in
+                        positions_by_item(&schedule, network_size).into_iter().enumerate()
+                    {
+                        assert_eq!(manager.matchups_containing(item as InputInt), positions);
+                    }
+                    assert!(manager.matchups_containing(network_size).is_empty());
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test_sharding {
+    use crate::gber::Decomposition;
+    use super::{LocalMatchupsManager, MatchupOrdering};
+    use crate::common_types::{BaseInt, InputInt};
+    use crate::error::SnicError;
+
+    fn manager(network_size: InputInt, base: BaseInt) -> LocalMatchupsManager {
+        LocalMatchupsManager::new(Decomposition::new(network_size, base).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn shards_partition_the_plan() {
+        for (network_size, base) in [(1003, 5), (100, 3), (21, 10), (2, 2)] {
+            for ordering in [MatchupOrdering::IntraFirst, MatchupOrdering::InterFirst] {
+                for shard_count in [1, 2, 3, 7, 50] {
+                    let plan = manager(network_size, base).with_ordering(ordering).with_seed(9);
+                    let shards = (0..shard_count)
+                        .map(|shard_index| plan.shard(shard_index, shard_count).unwrap())
+                        .collect::<Vec<_>>();
+                    let sizes = shards.iter().map(|shard| shard.size_hint().0).collect::<Vec<_>>();
+                    assert!(sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 1);
+                    let sharded = shards.into_iter().flatten().collect::<Vec<Vec<InputInt>>>();
+                    assert_eq!(sharded, plan.collect::<Vec<Vec<InputInt>>>());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shards_keep_to_few_subnetworks() {
+        let plan = manager(1003, 5);
+        let hub_size = plan.network_gber.stream_all_components().next().unwrap();
+        let first_shard = plan.shard(0, 2).unwrap();
+        assert!(first_shard.flatten().all(|item| item < hub_size));
+    }
+
+    #[test]
+    fn reject_missing_shards() {
+        for (shard_index, shard_count) in [(0, 0), (3, 3)] {
+            assert!(matches!(
+                manager(100, 3).shard(shard_index, shard_count),
+                Err(SnicError::InvalidParameter(_))
+            ));
+        }
+    }
 }
 
 
@@ -339,7 +1371,7 @@ mod test_matchup_allocations_for {
     fn base2() {
         let base = 2 as InputInt;
         let slice = &[(base.pow(10), 10u8), (base.pow(10), 10u8), (base, 1u8)];
-        for allocation in matchup_allocations_for(slice, base as BaseInt) {
+        for allocation in matchup_allocations_for(slice, base as BaseInt).unwrap() {
             assert_eq!(allocation, (1 as BaseInt, 1 as BaseInt))
         }
     }
@@ -349,7 +1381,7 @@ mod test_matchup_allocations_for {
     fn seat_allocation_equivalence() {
         let base = 10;
         for slice in [SHORT_SLICE, MID_SLICE, LONGER_SLICE] {
-            let allocations = matchup_allocations_for(slice, base);
+            let allocations = matchup_allocations_for(slice, base).unwrap();
             assert_eq!(
                 allocations.into_iter().map(|(a, b)| a + b).sum::<BaseInt>(),
                 (slice.len() as BaseInt - 1) * base,
@@ -359,10 +1391,10 @@ mod test_matchup_allocations_for {
 
     #[test]
     fn simple_10_base_components() {
-        assert_eq!(matchup_allocations_for(SHORT_SLICE, 10), vec![(6, 4)]);
-        assert_eq!(matchup_allocations_for(MID_SLICE, 10), vec![(6, 4), (7, 3)]);
+        assert_eq!(matchup_allocations_for(SHORT_SLICE, 10), Ok(vec![(6, 4)]));
+        assert_eq!(matchup_allocations_for(MID_SLICE, 10), Ok(vec![(6, 4), (7, 3)]));
         assert_eq!(
-            matchup_allocations_for(LONGER_SLICE, 10),
+            matchup_allocations_for(LONGER_SLICE, 10).unwrap(),
             vec![
                 (5, 5),
                 (5, 5),
@@ -384,27 +1416,39 @@ mod test_matchup_allocations_for {
 
 #[cfg(test)]
 mod test_take_elements_uniformly {
-    use super::take_elements_uniformly;
+    use super::{take_elements_uniformly, InputInt};
+    use crate::error::SnicError;
 
     #[test]
     fn take_2_of_20() {
         assert_eq!(
-            take_elements_uniformly(20, 2, 10).collect::<Vec<u32>>(),
+            take_elements_uniformly(20, 2, 10).unwrap().collect::<Vec<InputInt>>(),
             vec![10, 20],
         );
     }
     #[test]
     fn take_2_of_3() {
         assert_eq!(
-            take_elements_uniformly(3, 2, 100).collect::<Vec<u32>>(),
+            take_elements_uniformly(3, 2, 100).unwrap().collect::<Vec<InputInt>>(),
             vec![100, 101],
         );
     }
     #[test]
     fn take_5_of_1000() {
         assert_eq!(
-            take_elements_uniformly(1001, 5, 1).collect::<Vec<u32>>(),
+            take_elements_uniformly(1001, 5, 1).unwrap().collect::<Vec<InputInt>>(),
             vec![1, 201, 401, 601, 801],
         );
     }
+    #[test]
+    fn take_5_of_3() {
+        assert_eq!(
+            take_elements_uniformly(3, 5, 10).unwrap().collect::<Vec<InputInt>>(),
+            vec![10, 11, 12, 10, 11],
+        );
+    }
+    #[test]
+    fn take_from_nothing() {
+        assert!(matches!(take_elements_uniformly(0, 2, 0), Err(SnicError::EmptyNetwork)));
+    }
 }
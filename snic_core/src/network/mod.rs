@@ -1,3 +1,12 @@
 pub mod rank;
 pub mod matchup;
 pub mod comparison;
+pub mod bootstrap;
+pub mod statistics;
+pub mod tournament;
+pub mod adaptive;
+pub mod refinement;
+pub mod swiss;
+
+#[cfg(test)]
+mod test_helpers;
@@ -1,5 +1,8 @@
 use graph::prelude::*;
-use crate::common_types::InputInt;
+use crate::common_types::{BaseInt, InputInt};
+use crate::error::SnicError;
+use crate::network::comparison::{self, Comparison, ComparisonTally, MatchupResult};
+use crate::network::statistics;
 
 fn create_graph_from(
     vector_of_edges: Vec<(InputInt, InputInt)>
@@ -10,6 +13,8 @@ fn create_graph_from(
         .build()
 }
 
+/// Rank the items of (winner, loser) comparisons with PageRank.
+/// Edges run from winner to loser, so stronger items score lower.
 pub fn get_ranking_from(comparisons: Vec<(InputInt, InputInt)>) -> Vec<f32> {
     let (ranking, _iterations, _) = page_rank(
         &create_graph_from(comparisons),
@@ -17,3 +22,1103 @@ pub fn get_ranking_from(comparisons: Vec<(InputInt, InputInt)>) -> Vec<f32> {
     );
     ranking
 }
+
+/// Edge lists expanded on separate threads, read by the graph builder in place.
+#[cfg(feature = "parallel")]
+struct ChunkedEdges<EV> {
+    chunks: Vec<Vec<(InputInt, InputInt, EV)>>,
+}
+
+#[cfg(feature = "parallel")]
+impl<EV: Copy + Send + Sync> Edges for ChunkedEdges<EV> {
+    type NI = InputInt;
+    type EV = EV;
+    type EdgeIter<'a> = rayon::iter::Copied<
+        rayon::iter::Flatten<rayon::slice::Iter<'a, Vec<(InputInt, InputInt, EV)>>>,
+    > where EV: 'a;
+
+    fn edges(&self) -> Self::EdgeIter<'_> {
+        use rayon::prelude::*;
+        self.chunks.par_iter().flatten().copied()
+    }
+}
+
+/// Items ordered by score, with NaN scores last in either direction.
+/// Equal scores keep the order of the items.
+pub fn order_by_scores(scores: &[f32], descending: bool) -> Vec<InputInt> {
+    let mut items = (0..scores.len() as InputInt).collect::<Vec<InputInt>>();
+    items.sort_by(|&left, &right| {
+        let (left, right) = (scores[left as usize], scores[right as usize]);
+        match (left.is_nan(), right.is_nan()) {
+            (false, false) if descending => right.total_cmp(&left),
+            (false, false) => left.total_cmp(&right),
+            (left_nan, right_nan) => left_nan.cmp(&right_nan),
+        }
+    });
+    items
+}
+
+/// Place of an item in a ranking, with the comparisons behind it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Standing {
+    pub item: InputInt,
+    /// NaN for items the model left without a score.
+    pub score: f32,
+    /// Position in the ordering, 0 being the first.
+    pub rank: InputInt,
+    pub comparisons: u64,
+    pub wins: u64,
+    pub losses: u64,
+}
+
+impl Standing {
+    pub fn never_compared(&self) -> bool {
+        self.comparisons == 0
+    }
+}
+
+/// Standings of every item in scores or tally, in rank order.
+pub fn standings_from(scores: &[f32], tally: &ComparisonTally, descending: bool) -> Vec<Standing> {
+    let item_count = scores.len().max(tally.len());
+    let padded_scores = (0..item_count)
+        .map(|item| scores.get(item).copied().unwrap_or(f32::NAN))
+        .collect::<Vec<f32>>();
+    let count = |counts: &[u64], item: usize| counts.get(item).copied().unwrap_or(0);
+    order_by_scores(&padded_scores, descending)
+        .into_iter()
+        .enumerate()
+        .map(|(rank, item)| {
+            let index = item as usize;
+            Standing {
+                item,
+                score: padded_scores[index],
+                rank: rank as InputInt,
+                comparisons: count(&tally.comparisons, index),
+                wins: count(&tally.wins, index),
+                losses: count(&tally.losses, index),
+            }
+        })
+        .collect()
+}
+
+/// Ranking of a whole network from the results observed so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterimRanking {
+    /// Every item of the network, strongest first.
+    /// Items never compared come last, with a NaN score.
+    pub standings: Vec<Standing>,
+    /// Diagnostics of the model, with one score per item of the network.
+    pub outcome: RankingOutcome,
+    pub observed_comparisons: u64,
+    /// Comparisons of the full plan of the network.
+    pub planned_comparisons: u64,
+}
+
+impl InterimRanking {
+    /// Build from the outcome of any model over the results,
+    /// for instance `PlackettLuceRanker::rank_matchups`.
+    pub fn new(
+        network_size: InputInt,
+        base: BaseInt,
+        results: &[MatchupResult],
+        outcome: RankingOutcome,
+    ) -> Result<Self, SnicError> {
+        let comparisons = comparisons_within(network_size, results)?;
+        Self::from_comparisons(network_size, base, &comparisons, outcome)
+    }
+
+    fn from_comparisons(
+        network_size: InputInt,
+        base: BaseInt,
+        comparisons: &[Comparison],
+        mut outcome: RankingOutcome,
+    ) -> Result<Self, SnicError> {
+        let planned_comparisons =
+            statistics::calculate_plan_statistics_for(network_size, base)?.total_comparisons;
+        let tally = ComparisonTally::from_comparisons(comparisons);
+        // Scores of items without comparisons only reflect the model's defaults
+        outcome.scores.resize(network_size as usize, f32::NAN);
+        for (item, score) in outcome.scores.iter_mut().enumerate() {
+            if tally.comparisons.get(item).is_none_or(|&count| count == 0) {
+                *score = f32::NAN;
+            }
+        }
+        Ok(Self {
+            standings: standings_from(&outcome.scores, &tally, true),
+            outcome,
+            observed_comparisons: comparisons.len() as u64,
+            planned_comparisons,
+        })
+    }
+
+    /// Share of the planned comparisons observed, above 1 once results go beyond the plan.
+    pub fn coverage(&self) -> f64 {
+        if self.planned_comparisons == 0 {
+            return 1.0;
+        }
+        self.observed_comparisons as f64 / self.planned_comparisons as f64
+    }
+
+    /// Items without a single comparison, ascending.
+    pub fn never_compared(&self) -> Vec<InputInt> {
+        let mut items = self
+            .standings
+            .iter()
+            .filter(|standing| standing.never_compared())
+            .map(|standing| standing.item)
+            .collect::<Vec<InputInt>>();
+        items.sort_unstable();
+        items
+    }
+}
+
+/// Rank every item of a network declared up front, from however many results exist.
+/// Unlike `get_ranking_from`, items that have not appeared yet keep their place in the network.
+pub fn rank_interim(
+    network_size: InputInt,
+    base: BaseInt,
+    results: &[MatchupResult],
+    ranker: &impl Ranker,
+) -> Result<InterimRanking, SnicError> {
+    let comparisons = comparisons_within(network_size, results)?;
+    let outcome = ranker.rank(&comparisons);
+    InterimRanking::from_comparisons(network_size, base, &comparisons, outcome)
+}
+
+/// Comparisons of validated results whose items all belong to the network.
+fn comparisons_within(
+    network_size: InputInt,
+    results: &[MatchupResult],
+) -> Result<Vec<Comparison>, SnicError> {
+    let mut comparisons = vec![];
+    for (position, result) in results.iter().enumerate() {
+        result.validate(position)?;
+        if let Some(item) = result.items().find(|&item| item >= network_size) {
+            return Err(SnicError::InvalidRankings(format!(
+                "matchup {position} ranks item {item} outside the network of {network_size}"
+            )));
+        }
+        comparisons.extend(result.to_comparisons());
+    }
+    Ok(comparisons)
+}
+
+/// Per-item scores together with the diagnostics of the run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankingOutcome {
+    /// Indexed by item; a higher score means a stronger item.
+    pub scores: Vec<f32>,
+    pub iterations: usize,
+    /// Change of the scores in the last iteration, as the model measures it.
+    pub final_delta: f64,
+    pub converged: bool,
+}
+
+impl RankingOutcome {
+    /// Outcome of any model over no comparisons at all.
+    pub fn empty() -> Self {
+        Self {
+            scores: vec![],
+            iterations: 0,
+            final_delta: 0.0,
+            converged: true,
+        }
+    }
+}
+
+/// Model turning pairwise comparisons into per-item scores.
+/// Items are indexed up to the largest one seen in the comparisons.
+pub trait Ranker {
+    fn rank(&self, comparisons: &[Comparison]) -> RankingOutcome;
+}
+
+/// PageRank with losers linking to their winners, weighted by the comparisons.
+/// Tied items link to each other.
+/// Convergence is measured by the total absolute change of the scores.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PageRankRanker {
+    pub config: PageRankConfig,
+}
+
+impl Ranker for PageRankRanker {
+    fn rank(&self, comparisons: &[Comparison]) -> RankingOutcome {
+        if comparisons.is_empty() {
+            return RankingOutcome::empty();
+        }
+        let node_count = count_nodes_in(comparisons);
+        let reversed_edges = comparisons
+            .iter()
+            .filter(|comparison| comparison.weight > 0.0)
+            .flat_map(|comparison| {
+                let (winner, loser, weight) = (comparison.winner, comparison.loser, comparison.weight);
+                let backlink = comparison.tie.then_some((winner, loser, weight));
+                std::iter::once((loser, winner, weight)).chain(backlink)
+            });
+        let graph: DirectedCsrGraph<InputInt, (), f32> = GraphBuilder::new()
+            .csr_layout(CsrLayout::Sorted)
+            .edges_with_values(reversed_edges)
+            .build();
+        self.rank_graph(&graph, node_count)
+    }
+}
+
+impl PageRankRanker {
+    /// Rank strict orderings, best item first, like `rank` does their comparisons.
+    /// Optional `weights`, one positive value per matchup, weigh all its comparisons.
+    /// With the `parallel` feature, the matchups are expanded on all threads
+    /// straight into the edges of the graph, skipping the intermediate comparisons.
+    /// Each thread keeps its own edge list, and the graph builder reads them all in turn.
+    pub fn rank_matchups<R: AsRef<[InputInt]> + Sync>(
+        &self,
+        ranked_matchups: &[R],
+        weights: Option<&[f32]>,
+    ) -> Result<RankingOutcome, SnicError> {
+        #[cfg(feature = "parallel")]
+        {
+            if let Some(weights) = weights {
+                comparison::check_weights(ranked_matchups.len(), weights)?;
+            }
+            let chunks = comparison::par_expand_ranked_matchups_in_chunks_with(
+                ranked_matchups,
+                |position, winner, loser| {
+                    (loser, winner, weights.map_or(1.0, |weights| weights[position]))
+                },
+            )?;
+            let graph: DirectedCsrGraph<InputInt, (), f32> =
+                DirectedCsrGraph::from((ChunkedEdges { chunks }, CsrLayout::Sorted));
+            let node_count = graph.node_count().index();
+            Ok(self.rank_graph(&graph, node_count))
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            let comparisons = match weights {
+                Some(weights) => comparison::expand_weighted_matchups(ranked_matchups, weights)?,
+                None => comparison::expand_ranked_matchups(ranked_matchups)?,
+            };
+            Ok(self.rank(&comparisons))
+        }
+    }
+
+    fn rank_graph(
+        &self,
+        graph: &DirectedCsrGraph<InputInt, (), f32>,
+        node_count: usize,
+    ) -> RankingOutcome {
+        let (scores, iterations, final_delta) = weighted_page_rank(graph, node_count, self.config);
+        RankingOutcome {
+            scores,
+            iterations,
+            final_delta,
+            converged: final_delta < self.config.tolerance,
+        }
+    }
+}
+
+/// Power iteration in which every node passes its score on
+/// in proportion to the weights of its outgoing edges.
+/// Every iteration reads only the scores of the previous one,
+/// so with the `parallel` feature the nodes are updated on all threads.
+fn weighted_page_rank(
+    graph: &DirectedCsrGraph<InputInt, (), f32>,
+    node_count: usize,
+    config: PageRankConfig,
+) -> (Vec<f32>, usize, f64) {
+    let graph_nodes = graph.node_count().index();
+    let base_score = (1.0 - config.damping_factor) / node_count as f32;
+    let out_weights: Vec<f32> = (0..graph_nodes)
+        .map(|node| {
+            graph
+                .out_neighbors_with_values(node as InputInt)
+                .map(|edge| edge.value)
+                .sum()
+        })
+        .collect();
+    let mut scores = vec![1.0 / node_count as f32; node_count];
+    let mut iterations = 0;
+    loop {
+        // Score passed along each unit of outgoing weight
+        let shares: Vec<f32> = (0..graph_nodes)
+            .map(|node| match out_weights[node] {
+                0.0 => 0.0,
+                out_weight => scores[node] / out_weight,
+            })
+            .collect();
+        let next_score_of = |node: usize| {
+            let incoming_total = if node < graph_nodes {
+                graph
+                    .in_neighbors_with_values(node as InputInt)
+                    .map(|edge| shares[edge.target as usize] * edge.value)
+                    .sum::<f32>()
+            } else {
+                0.0
+            };
+            base_score + config.damping_factor * incoming_total
+        };
+        #[cfg(feature = "parallel")]
+        let next_scores: Vec<f32> = {
+            use rayon::prelude::*;
+            (0..node_count).into_par_iter().map(next_score_of).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let next_scores: Vec<f32> = (0..node_count).map(next_score_of).collect();
+        let error: f64 = next_scores
+            .iter()
+            .zip(&scores)
+            .map(|(next_score, score)| (next_score - score).abs() as f64)
+            .sum();
+        scores = next_scores;
+        iterations += 1;
+        if error < config.tolerance || iterations >= config.max_iterations {
+            return (scores, iterations, error);
+        }
+    }
+}
+
+/// Bradley–Terry maximum likelihood, fitted with minorization–maximization.
+/// Every item plays `prior_strength` virtual wins and losses
+/// against a fixed opponent of worth 1,
+/// which keeps the worth of undefeated and winless items finite.
+/// A tie counts as half a win for each side,
+/// and every comparison counts as much as its weight.
+/// Scores are the fitted worths;
+/// convergence is measured by the largest change of a log-worth.
+/// Without a prior, items never compared keep a worth of 1 and do not weigh on the others.
+/// A negative prior yields NaN scores that never count as converged;
+/// `new` rejects it up front.
+#[derive(Debug, Clone, Copy)]
+pub struct BradleyTerryRanker {
+    pub max_iterations: usize,
+    pub tolerance: f64,
+    pub prior_strength: f64,
+}
+
+impl BradleyTerryRanker {
+    pub const DEFAULT_MAX_ITERATIONS: usize = 10_000;
+    pub const DEFAULT_TOLERANCE: f64 = 1E-5;
+    pub const DEFAULT_PRIOR_STRENGTH: f64 = 0.5;
+
+    pub fn new(prior_strength: f64) -> Result<Self, SnicError> {
+        check_prior_strength(prior_strength)?;
+        Ok(Self {
+            prior_strength,
+            ..Default::default()
+        })
+    }
+}
+
+impl Default for BradleyTerryRanker {
+    fn default() -> Self {
+        Self {
+            max_iterations: Self::DEFAULT_MAX_ITERATIONS,
+            tolerance: Self::DEFAULT_TOLERANCE,
+            prior_strength: Self::DEFAULT_PRIOR_STRENGTH,
+        }
+    }
+}
+
+impl Ranker for BradleyTerryRanker {
+    fn rank(&self, comparisons: &[Comparison]) -> RankingOutcome {
+        let node_count = count_nodes_in(comparisons);
+        if check_prior_strength(self.prior_strength).is_err() {
+            return RankingOutcome {
+                scores: vec![f32::NAN; node_count],
+                iterations: 0,
+                final_delta: f64::INFINITY,
+                converged: false,
+            };
+        }
+        let mut wins = vec![self.prior_strength; node_count];
+        for comparison in comparisons {
+            let weight = comparison.weight as f64;
+            if comparison.tie {
+                wins[comparison.winner as usize] += 0.5 * weight;
+                wins[comparison.loser as usize] += 0.5 * weight;
+            } else {
+                wins[comparison.winner as usize] += weight;
+            }
+        }
+        let mut worths = vec![1f64; node_count];
+        let mut denominators = vec![0f64; node_count];
+        let mut iterations = 0;
+        let mut final_delta = f64::INFINITY;
+        while iterations < self.max_iterations && final_delta >= self.tolerance {
+            reset_to_prior(&mut denominators, &worths, self.prior_strength);
+            for comparison in comparisons {
+                let (winner, loser) = (comparison.winner as usize, comparison.loser as usize);
+                let share = comparison.weight as f64 / (worths[winner] + worths[loser]);
+                denominators[winner] += share;
+                denominators[loser] += share;
+            }
+            let next_worths =
+                minorized_worths_from(&wins, &denominators, &worths, self.prior_strength);
+            final_delta = largest_log_change(&next_worths, &worths);
+            worths = next_worths;
+            iterations += 1;
+        }
+        RankingOutcome {
+            scores: worths.into_iter().map(|worth| worth as f32).collect(),
+            iterations,
+            final_delta,
+            converged: final_delta < self.tolerance,
+        }
+    }
+}
+
+/// Plackett–Luce worths with the standard errors of their logarithms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlackettLuceOutcome {
+    /// Scores are the fitted worths.
+    pub ranking: RankingOutcome,
+    /// Taken from the diagonal of the Fisher information of the log-worths,
+    /// so covariances between items are ignored.
+    pub standard_errors: Vec<f32>,
+}
+
+/// Plackett–Luce maximum likelihood over full matchup orderings,
+/// fitted with minorization–maximization.
+/// Each ranked matchup, best item first, is one listwise observation:
+/// the winner is chosen from all items, the runner-up from the rest, and so on.
+/// Tied items are each chosen from the same remaining items,
+/// and unranked items are never chosen.
+/// Every observation counts as much as the weight of its matchup.
+/// The prior is the same virtual opponent as in `BradleyTerryRanker`, and cannot be negative.
+/// Without it, items never observed keep a worth of 1 and an infinite standard error.
+#[derive(Debug, Clone, Copy)]
+pub struct PlackettLuceRanker {
+    pub max_iterations: usize,
+    pub tolerance: f64,
+    pub prior_strength: f64,
+}
+
+impl Default for PlackettLuceRanker {
+    fn default() -> Self {
+        Self {
+            max_iterations: BradleyTerryRanker::DEFAULT_MAX_ITERATIONS,
+            tolerance: BradleyTerryRanker::DEFAULT_TOLERANCE,
+            prior_strength: BradleyTerryRanker::DEFAULT_PRIOR_STRENGTH,
+        }
+    }
+}
+
+impl PlackettLuceRanker {
+    pub fn rank_matchups(
+        &self,
+        matchup_results: &[MatchupResult],
+    ) -> Result<PlackettLuceOutcome, SnicError> {
+        check_prior_strength(self.prior_strength)?;
+        // Validation only, the pairwise expansion itself is not needed
+        comparison::expand_matchup_results(matchup_results)?;
+        let observations: Vec<ListwiseObservation> =
+            matchup_results.iter().map(ListwiseObservation::from).collect();
+        let node_count = observations
+            .iter()
+            .flat_map(|observation| &observation.items)
+            .map(|&item| item as usize + 1)
+            .max()
+            .unwrap_or(0);
+        let mut wins = vec![self.prior_strength; node_count];
+        for observation in &observations {
+            for &(start, choosers) in &observation.stages {
+                for &item in &observation.items[start..start + choosers] {
+                    wins[item as usize] += observation.weight;
+                }
+            }
+        }
+        let mut worths = vec![1f64; node_count];
+        let mut denominators = vec![0f64; node_count];
+        let mut iterations = 0;
+        let mut final_delta = f64::INFINITY;
+        while iterations < self.max_iterations && final_delta >= self.tolerance {
+            reset_to_prior(&mut denominators, &worths, self.prior_strength);
+            for observation in &observations {
+                let stage_totals = stage_totals_for(&observation.items, &worths);
+                let mut stages = observation.stages.iter().peekable();
+                let mut cumulative_share = 0.0;
+                for (position, &item) in observation.items.iter().enumerate() {
+                    while let Some(&&(start, choosers)) = stages.peek() {
+                        if start > position {
+                            break;
+                        }
+                        cumulative_share += observation.weight * choosers as f64 / stage_totals[start];
+                        stages.next();
+                    }
+                    denominators[item as usize] += cumulative_share;
+                }
+            }
+            let next_worths =
+                minorized_worths_from(&wins, &denominators, &worths, self.prior_strength);
+            final_delta = largest_log_change(&next_worths, &worths);
+            worths = next_worths;
+            iterations += 1;
+        }
+        let standard_errors = self.standard_errors_for(&observations, &worths);
+        Ok(PlackettLuceOutcome {
+            ranking: RankingOutcome {
+                scores: worths.into_iter().map(|worth| worth as f32).collect(),
+                iterations,
+                final_delta,
+                converged: final_delta < self.tolerance,
+            },
+            standard_errors,
+        })
+    }
+
+    fn standard_errors_for(&self, observations: &[ListwiseObservation], worths: &[f64]) -> Vec<f32> {
+        let mut information: Vec<f64> = worths
+            .iter()
+            .map(|worth| {
+                let probability = worth / (worth + 1.0);
+                2.0 * self.prior_strength * probability * (1.0 - probability)
+            })
+            .collect();
+        for observation in observations {
+            let stage_totals = stage_totals_for(&observation.items, worths);
+            for (position, &item) in observation.items.iter().enumerate() {
+                let worth = worths[item as usize];
+                information[item as usize] += observation
+                    .stages
+                    .iter()
+                    .take_while(|(start, _)| *start <= position)
+                    .map(|&(start, choosers)| {
+                        let probability = worth / stage_totals[start];
+                        observation.weight * choosers as f64 * probability * (1.0 - probability)
+                    })
+                    .sum::<f64>();
+            }
+        }
+        information
+            .into_iter()
+            .map(|information| (1.0 / information.sqrt()) as f32)
+            .collect()
+    }
+}
+
+/// Matchup result laid out as a sequence of choices.
+/// Every tier is one stage whose items are all chosen from the items left,
+/// so tied items share their stage.
+/// A lone item left at the end is not a choice and gets no stage.
+struct ListwiseObservation {
+    /// Tiers from best to worst, then the unranked items.
+    items: Vec<InputInt>,
+    /// Where each stage starts in the items, and how many of them it chooses.
+    stages: Vec<(usize, usize)>,
+    weight: f64,
+}
+
+impl From<&MatchupResult> for ListwiseObservation {
+    fn from(result: &MatchupResult) -> Self {
+        let items: Vec<InputInt> = result.items().collect();
+        let mut stages = vec![];
+        let mut start = 0;
+        for tier in &result.tiers {
+            if items.len() - start > 1 {
+                stages.push((start, tier.len()));
+            }
+            start += tier.len();
+        }
+        Self {
+            items,
+            stages,
+            weight: result.weight as f64,
+        }
+    }
+}
+
+/// Total worth of the items still to be chosen at every position of a ranking.
+fn stage_totals_for(ranking: &[InputInt], worths: &[f64]) -> Vec<f64> {
+    let mut stage_totals: Vec<f64> = ranking
+        .iter()
+        .rev()
+        .scan(0.0, |total, &item| {
+            *total += worths[item as usize];
+            Some(*total)
+        })
+        .collect();
+    stage_totals.reverse();
+    stage_totals
+}
+
+/// Seed the MM denominators with the games against the virtual opponent.
+fn reset_to_prior(denominators: &mut [f64], worths: &[f64], prior_strength: f64) {
+    denominators
+        .iter_mut()
+        .zip(worths)
+        .for_each(|(denominator, worth)| *denominator = 2.0 * prior_strength / (worth + 1.0));
+}
+
+/// The prior is a number of virtual games, so it cannot be negative.
+fn check_prior_strength(prior_strength: f64) -> Result<(), SnicError> {
+    if !(prior_strength.is_finite() && prior_strength >= 0.0) {
+        return Err(SnicError::InvalidParameter(format!(
+            "the prior strength must be a finite, non-negative number, not {prior_strength}"
+        )));
+    }
+    Ok(())
+}
+
+/// Items without any game, which only happens without a prior, keep their worth
+/// and are left out of the mean.
+fn minorized_worths_from(
+    wins: &[f64],
+    denominators: &[f64],
+    worths: &[f64],
+    prior_strength: f64,
+) -> Vec<f64> {
+    let mut next_worths: Vec<f64> = wins
+        .iter()
+        .zip(denominators)
+        .zip(worths)
+        .map(|((wins, denominator), worth)| {
+            if *denominator > 0.0 {
+                wins / denominator
+            } else {
+                *worth
+            }
+        })
+        .collect();
+    if prior_strength <= 0.0 {
+        // Without the virtual opponent the scale is free, so pin the mean
+        let played = || denominators.iter().map(|denominator| *denominator > 0.0);
+        let (total, count) = next_worths
+            .iter()
+            .zip(played())
+            .filter(|(_, played)| *played)
+            .fold((0.0, 0usize), |(total, count), (worth, _)| (total + worth, count + 1));
+        if count > 0 {
+            let mean = total / count as f64;
+            next_worths
+                .iter_mut()
+                .zip(played())
+                .filter(|(_, played)| *played)
+                .for_each(|(worth, _)| *worth /= mean);
+        }
+    }
+    next_worths
+}
+
+/// A worth turning non-finite counts as an infinite change, so the fit never converges on it.
+fn largest_log_change(next_worths: &[f64], worths: &[f64]) -> f64 {
+    next_worths
+        .iter()
+        .zip(worths)
+        .map(|(next, previous)| {
+            if next == previous {
+                return 0.0;
+            }
+            let change = (next / previous).ln().abs();
+            if change.is_finite() {
+                change
+            } else {
+                f64::INFINITY
+            }
+        })
+        .fold(0.0, f64::max)
+}
+
+fn count_nodes_in(comparisons: &[Comparison]) -> usize {
+    comparisons
+        .iter()
+        .map(|comparison| comparison.winner.max(comparison.loser) as usize + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+
+#[cfg(test)]
+mod test_bradley_terry {
+    use super::{largest_log_change, BradleyTerryRanker, Ranker};
+    use crate::error::SnicError;
+    use crate::network::comparison::Comparison;
+    use crate::network::test_helpers::strict_comparisons;
+
+    #[test]
+    fn follow_a_chain() {
+        let comparisons = strict_comparisons(&[(0, 1), (1, 2), (0, 2), (2, 3), (1, 3)]);
+        let outcome = BradleyTerryRanker::default().rank(&comparisons);
+        assert!(outcome.converged);
+        assert!(outcome.scores.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+
+    #[test]
+    fn undefeated_items_stay_finite() {
+        let outcome = BradleyTerryRanker::default().rank(&strict_comparisons(&[(0, 1); 10]));
+        assert!(outcome.converged);
+        assert!(outcome.scores.iter().all(|score| score.is_finite() && *score > 0.0));
+    }
+
+    #[test]
+    fn recover_balanced_record() {
+        let ranker = BradleyTerryRanker {
+            prior_strength: 0.0,
+            ..Default::default()
+        };
+        let outcome = ranker.rank(&strict_comparisons(&[(0, 1), (1, 0), (0, 1), (1, 0)]));
+        assert!(outcome.converged);
+        assert!((outcome.scores[0] - outcome.scores[1]).abs() < 1E-5);
+    }
+
+    #[test]
+    fn ties_pull_together() {
+        let mut comparisons = strict_comparisons(&[(0, 1), (0, 1), (0, 1)]);
+        let apart = BradleyTerryRanker::default().rank(&comparisons);
+        comparisons.extend([Comparison::tie_between(0, 1); 3]);
+        let tied = BradleyTerryRanker::default().rank(&comparisons);
+        assert!(tied.scores[0] > tied.scores[1]);
+        assert!(tied.scores[0] / tied.scores[1] < apart.scores[0] / apart.scores[1]);
+    }
+
+    #[test]
+    fn weights_scale_evidence() {
+        let mut comparisons = strict_comparisons(&[(0, 1), (1, 0)]);
+        comparisons[0] = comparisons[0].with_weight(3.0);
+        let outcome = BradleyTerryRanker {
+            prior_strength: 0.0,
+            ..Default::default()
+        }
+        .rank(&comparisons);
+        // As if the first win had been observed three times
+        assert!((outcome.scores[0] / outcome.scores[1] - 3.0).abs() < 1E-3);
+    }
+
+    #[test]
+    fn report_exhausted_iterations() {
+        let ranker = BradleyTerryRanker {
+            max_iterations: 1,
+            ..Default::default()
+        };
+        let outcome = ranker.rank(&strict_comparisons(&[(0, 1), (1, 2)]));
+        assert_eq!(outcome.iterations, 1);
+        assert!(!outcome.converged);
+    }
+
+    #[test]
+    fn nothing_to_rank() {
+        let outcome = BradleyTerryRanker::default().rank(&[]);
+        assert!(outcome.scores.is_empty());
+        assert!(outcome.converged);
+    }
+
+    #[test]
+    fn leave_items_never_compared_out_without_prior() {
+        let ranker = BradleyTerryRanker::new(0.0).unwrap();
+        // Item 1 never plays
+        let outcome = ranker.rank(&strict_comparisons(&[(0, 2), (2, 0), (0, 2)]));
+        assert!(outcome.converged);
+        assert!(outcome.scores.iter().all(|score| score.is_finite()));
+        assert_eq!(outcome.scores[1], 1.0);
+        assert!((outcome.scores[0] / outcome.scores[2] - 2.0).abs() < 1E-3);
+    }
+
+    #[test]
+    fn reject_negative_priors() {
+        assert!(matches!(BradleyTerryRanker::new(-1.0), Err(SnicError::InvalidParameter(_))));
+        assert!(matches!(BradleyTerryRanker::new(f64::NAN), Err(SnicError::InvalidParameter(_))));
+        let outcome = BradleyTerryRanker {
+            prior_strength: -1.0,
+            ..Default::default()
+        }
+        .rank(&strict_comparisons(&[(0, 1), (1, 2)]));
+        assert!(!outcome.converged);
+        assert!(outcome.scores.iter().all(|score| score.is_nan()));
+    }
+
+    #[test]
+    fn never_converge_on_non_finite_worths() {
+        assert_eq!(largest_log_change(&[1.0, f64::NAN], &[1.0, 1.0]), f64::INFINITY);
+        assert_eq!(largest_log_change(&[0.0, 2.0], &[0.0, 2.0]), 0.0);
+    }
+}
+
+
+#[cfg(test)]
+mod test_plackett_luce {
+    use super::PlackettLuceRanker;
+    use crate::error::SnicError;
+    use crate::network::comparison::MatchupResult;
+    use crate::network::test_helpers::strict_results;
+
+    #[test]
+    fn follow_consistent_orderings() {
+        let matchups = strict_results(&[vec![0, 1, 2], vec![1, 2, 3], vec![0, 2, 3], vec![0, 1, 3]]);
+        let outcome = PlackettLuceRanker::default().rank_matchups(&matchups).unwrap();
+        assert!(outcome.ranking.converged);
+        assert!(outcome.ranking.scores.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+
+    #[test]
+    fn reduce_to_pairs() {
+        let ranker = PlackettLuceRanker {
+            prior_strength: 0.0,
+            ..Default::default()
+        };
+        let matchups = strict_results(&[vec![0, 1], vec![0, 1], vec![1, 0]]);
+        let outcome = ranker.rank_matchups(&matchups).unwrap();
+        // Two wins out of three make the odds 2:1
+        assert!((outcome.ranking.scores[0] / outcome.ranking.scores[1] - 2.0).abs() < 1E-3);
+    }
+
+    #[test]
+    fn leave_items_never_observed_out_without_prior() {
+        let ranker = PlackettLuceRanker {
+            prior_strength: 0.0,
+            ..Default::default()
+        };
+        // Item 1 never plays
+        let matchups = strict_results(&[vec![0, 2], vec![0, 2], vec![2, 0]]);
+        let outcome = ranker.rank_matchups(&matchups).unwrap();
+        assert!(outcome.ranking.converged);
+        assert!(outcome.ranking.scores.iter().all(|score| score.is_finite()));
+        assert_eq!(outcome.ranking.scores[1], 1.0);
+        assert!((outcome.ranking.scores[0] / outcome.ranking.scores[2] - 2.0).abs() < 1E-3);
+        assert!(outcome.standard_errors[0].is_finite());
+        assert_eq!(outcome.standard_errors[1], f32::INFINITY);
+    }
+
+    #[test]
+    fn reject_negative_priors() {
+        let ranker = PlackettLuceRanker {
+            prior_strength: -1.0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            ranker.rank_matchups(&strict_results(&[vec![0, 1, 2]])),
+            Err(SnicError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn shrink_errors_with_evidence() {
+        let ranker = PlackettLuceRanker::default();
+        let matchups = strict_results(&[vec![0, 1, 2], vec![2, 1, 0]]);
+        let few = ranker.rank_matchups(&matchups).unwrap();
+        let many = ranker.rank_matchups(&matchups.iter().cycle().take(40).cloned().collect::<Vec<_>>()).unwrap();
+        for item in 0..3 {
+            assert!(many.standard_errors[item] < few.standard_errors[item]);
+        }
+    }
+
+    #[test]
+    fn honour_ties_and_truncation() {
+        let results = [
+            MatchupResult { tiers: vec![vec![0], vec![1, 2]], ..Default::default() },
+            MatchupResult { tiers: vec![vec![0]], unranked: vec![1, 2], ..Default::default() },
+            MatchupResult { tiers: vec![vec![1, 2]], unranked: vec![3], ..Default::default() },
+        ];
+        let outcome = PlackettLuceRanker::default().rank_matchups(&results).unwrap();
+        let scores = outcome.ranking.scores;
+        assert!(outcome.ranking.converged);
+        assert!(scores[0] > scores[1] && scores[2] > scores[3]);
+        assert!((scores[1] - scores[2]).abs() < 1E-4);
+    }
+
+    #[test]
+    fn weights_scale_evidence() {
+        let mut matchups = strict_results(&[vec![0, 1], vec![1, 0]]);
+        matchups[0].weight = 3.0;
+        let outcome = PlackettLuceRanker {
+            prior_strength: 0.0,
+            ..Default::default()
+        }
+        .rank_matchups(&matchups)
+        .unwrap();
+        assert!((outcome.ranking.scores[0] / outcome.ranking.scores[1] - 3.0).abs() < 1E-3);
+    }
+
+    #[test]
+    fn reject_malformed_matchups() {
+        for matchups in [vec![vec![0]], vec![vec![0, 1, 0]]] {
+            assert!(matches!(
+                PlackettLuceRanker::default().rank_matchups(&strict_results(&matchups)),
+                Err(SnicError::InvalidRankings(_))
+            ));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test_page_rank {
+    use super::{PageRankConfig, PageRankRanker, Ranker};
+    use crate::network::comparison::Comparison;
+
+    #[test]
+    fn favour_heavier_wins() {
+        // 2 beats 0 with more conviction than 1 does
+        let comparisons = [
+            Comparison::from((1, 0)).with_weight(0.2),
+            Comparison::from((2, 0)).with_weight(2.0),
+            Comparison::tie_between(1, 2),
+        ];
+        let ranker = PageRankRanker {
+            config: PageRankConfig::new(200, 1E-6, 0.85),
+        };
+        let outcome = ranker.rank(&comparisons);
+        assert!(outcome.converged);
+        assert!(outcome.scores[2] > outcome.scores[1]);
+        assert!(outcome.scores[1] > outcome.scores[0]);
+    }
+
+    #[test]
+    fn score_items_without_edges() {
+        let outcome = PageRankRanker::default().rank(&[Comparison::from((3, 2)).with_weight(0.0)]);
+        assert_eq!(outcome.scores.len(), 4);
+        assert!(outcome.scores.iter().all(|score| score.is_finite()));
+    }
+}
+
+#[cfg(test)]
+mod test_page_rank_of_matchups {
+    use super::{PageRankRanker, Ranker};
+    use crate::common_types::InputInt;
+    use crate::error::SnicError;
+    use crate::gber::Decomposition;
+    use crate::network::comparison;
+    use crate::network::matchup::LocalMatchupsManager;
+
+    fn planned_matchups() -> Vec<Vec<InputInt>> {
+        LocalMatchupsManager::new(Decomposition::new(500, 4).unwrap())
+            .unwrap()
+            .with_seed(1)
+            .collect()
+    }
+
+    #[test]
+    fn match_ranking_of_comparisons() {
+        let ranked_matchups = planned_matchups();
+        let ranker = PageRankRanker::default();
+        assert_eq!(
+            ranker.rank_matchups(&ranked_matchups, None).unwrap(),
+            ranker.rank(&comparison::expand_ranked_matchups(&ranked_matchups).unwrap())
+        );
+    }
+
+    #[test]
+    fn match_ranking_of_weighted_comparisons() {
+        let ranked_matchups = planned_matchups();
+        let weights = (0..ranked_matchups.len())
+            .map(|position| 1.0 + (position % 3) as f32)
+            .collect::<Vec<f32>>();
+        let ranker = PageRankRanker::default();
+        let outcome = ranker.rank_matchups(&ranked_matchups, Some(&weights)).unwrap();
+        let expected = ranker.rank(
+            &comparison::expand_weighted_matchups(&ranked_matchups, &weights).unwrap(),
+        );
+        assert_eq!(outcome.iterations, expected.iterations);
+        for (score, expected_score) in outcome.scores.iter().zip(&expected.scores) {
+            assert!((score - expected_score).abs() < 1E-6);
+        }
+    }
+
+    #[test]
+    fn reject_malformed_matchups() {
+        let ranker = PageRankRanker::default();
+        assert!(matches!(
+            ranker.rank_matchups(&[vec![0, 1, 2], vec![3, 4]], None),
+            Err(SnicError::InvalidRankings(_))
+        ));
+        for weights in [vec![1.0], vec![1.0, 0.0]] {
+            assert!(matches!(
+                ranker.rank_matchups(&[vec![0, 1], vec![2, 3]], Some(&weights)),
+                Err(SnicError::InvalidRankings(_))
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_standings {
+    use super::{order_by_scores, standings_from};
+    use crate::network::comparison::{Comparison, ComparisonTally};
+
+    #[test]
+    fn order_nan_last_both_ways() {
+        let scores = [0.5, f32::NAN, 0.1, 0.9, f32::NAN, 0.5];
+        assert_eq!(order_by_scores(&scores, false), vec![2, 0, 5, 3, 1, 4]);
+        assert_eq!(order_by_scores(&scores, true), vec![3, 0, 5, 2, 1, 4]);
+    }
+
+    #[test]
+    fn tally_matchups_like_comparisons() {
+        let ranked_matchups = [vec![2, 0, 1], vec![1, 3, 0]];
+        let comparisons = ranked_matchups
+            .iter()
+            .flat_map(|ranking| {
+                (0..ranking.len()).flat_map(move |left| {
+                    (left + 1..ranking.len())
+                        .map(move |right| Comparison::from((ranking[left], ranking[right])))
+                })
+            })
+            .collect::<Vec<Comparison>>();
+        let tally = ComparisonTally::from_ranked_matchups(&ranked_matchups);
+        assert_eq!(tally, ComparisonTally::from_comparisons(&comparisons));
+        assert_eq!(tally.wins, vec![1, 2, 2, 1]);
+        assert_eq!(tally.losses, vec![3, 2, 0, 1]);
+    }
+
+    #[test]
+    fn stand_every_item() {
+        let comparisons = [
+            Comparison::from((3, 0)),
+            Comparison::tie_between(0, 1),
+        ];
+        let tally = ComparisonTally::from_comparisons(&comparisons);
+        let standings = standings_from(&[0.2, 0.3, 0.1], &tally, true);
+        let items = standings.iter().map(|standing| standing.item).collect::<Vec<_>>();
+        assert_eq!(items, vec![1, 0, 2, 3]);
+        assert_eq!((standings[1].comparisons, standings[1].wins, standings[1].losses), (2, 0, 1));
+        assert_eq!((standings[0].comparisons, standings[0].wins), (1, 0));
+        assert!(standings[2].never_compared());
+        assert!(standings[3].score.is_nan());
+        assert_eq!(standings[3].wins, 1);
+        assert!(standings.iter().enumerate().all(|(rank, standing)| standing.rank as usize == rank));
+    }
+}
+
+#[cfg(test)]
+mod test_interim_ranking {
+    use super::{rank_interim, BradleyTerryRanker, InterimRanking, PageRankRanker, Ranker};
+    use crate::error::SnicError;
+    use crate::network::comparison::{self, MatchupResult};
+
+    #[test]
+    fn score_every_declared_item() {
+        let results = [MatchupResult::from(vec![4, 2, 0])];
+        let ranking = rank_interim(9, 3, &results, &PageRankRanker::default()).unwrap();
+        assert_eq!(ranking.outcome.scores.len(), 9);
+        let items = ranking.standings.iter().map(|standing| standing.item).collect::<Vec<_>>();
+        assert_eq!(items[..3], [4, 2, 0]);
+        assert_eq!(ranking.never_compared(), vec![1, 3, 5, 6, 7, 8]);
+        assert!(ranking.standings[3..].iter().all(|standing| standing.score.is_nan()));
+    }
+
+    #[test]
+    fn report_coverage_of_the_plan() {
+        // 9 items in base 3 take 6 matchups of 3 comparisons
+        let results = [MatchupResult::from(vec![0, 1, 2]), MatchupResult::from(vec![3, 4, 5])];
+        let ranking = rank_interim(9, 3, &results, &BradleyTerryRanker::default()).unwrap();
+        assert_eq!((ranking.observed_comparisons, ranking.planned_comparisons), (6, 18));
+        assert!((ranking.coverage() - 1.0 / 3.0).abs() < 1e-12);
+        let empty = rank_interim(9, 3, &[], &BradleyTerryRanker::default()).unwrap();
+        assert_eq!(empty.coverage(), 0.0);
+        assert_eq!(empty.never_compared().len(), 9);
+    }
+
+    #[test]
+    fn reject_items_outside_the_network() {
+        let results = [MatchupResult::from(vec![0, 9, 2])];
+        assert!(matches!(
+            rank_interim(9, 3, &results, &BradleyTerryRanker::default()),
+            Err(SnicError::InvalidRankings(_))
+        ));
+    }
+
+    #[test]
+    fn accept_outcomes_of_any_model() {
+        let results = [MatchupResult::from(vec![2, 1, 0])];
+        let outcome = BradleyTerryRanker::default()
+            .rank(&comparison::expand_matchup_results(&results).unwrap());
+        let ranking = InterimRanking::new(4, 2, &results, outcome).unwrap();
+        assert_eq!(ranking.standings.len(), 4);
+        assert_eq!(ranking.never_compared(), vec![3]);
+    }
+}
@@ -0,0 +1,177 @@
+//! Extra rounds spent only on the items that could still make the top k.
+//! Candidates are the items whose bootstrapped rank interval reaches into the top k.
+//! They play a fresh SNIC plan among themselves, numbered from the strongest,
+//! and its results are ranked together with the global ones.
+use crate::common_types::{BaseInt, InputInt};
+use crate::error::SnicError;
+use crate::gber::Decomposition;
+use crate::network::bootstrap::BootstrapOutcome;
+use crate::network::comparison::MatchupResult;
+use crate::network::matchup::LocalMatchupsManager;
+use crate::network::rank::{self, InterimRanking, Ranker};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopKRefinement {
+    pub k: InputInt,
+    /// Strongest first; item `i` of the sub-tournament is `candidates[i]`.
+    pub candidates: Vec<InputInt>,
+    /// Decomposition of the candidates, driving the sub-tournament.
+    pub decomposition: Decomposition,
+    network_size: InputInt,
+}
+
+impl TopKRefinement {
+    /// Select the candidates from the current ranking and its rank intervals.
+    /// The top k of the point ranking always take part,
+    /// and the next best items fill in when fewer than `base` are plausible.
+    /// Items never compared are left out, so rank them first.
+    pub fn new(
+        current: &BootstrapOutcome,
+        network_size: InputInt,
+        base: BaseInt,
+        k: InputInt,
+    ) -> Result<Self, SnicError> {
+        if k == 0 {
+            return Err(SnicError::InvalidParameter("k must be at least 1".into()));
+        }
+        if current.point.scores.len() > network_size as usize {
+            return Err(SnicError::InvalidParameter(format!(
+                "the ranking holds {} items, more than the network of {network_size}",
+                current.point.scores.len()
+            )));
+        }
+        let compared = rank::order_by_scores(&current.point.scores, true)
+            .into_iter()
+            .filter(|&item| current.appearances[item as usize] > 0)
+            .collect::<Vec<InputInt>>();
+        let mut candidates = vec![];
+        for (position, &item) in compared.iter().enumerate() {
+            let plausible = current.rank_intervals[item as usize].0 < k;
+            if (position as InputInt) < k || plausible || candidates.len() < base as usize {
+                candidates.push(item);
+            }
+        }
+        let decomposition = Decomposition::new(candidates.len() as InputInt, base)?;
+        // Also rejects candidates too few for a single matchup
+        LocalMatchupsManager::new(decomposition.clone())?;
+        Ok(Self {
+            k,
+            candidates,
+            decomposition,
+            network_size,
+        })
+    }
+
+    /// Matchups of the sub-tournament, in network items.
+    pub fn matchups(&self) -> impl Iterator<Item = Vec<InputInt>> + '_ {
+        LocalMatchupsManager::new(self.decomposition.clone())
+            .expect("the decomposition was checked on creation")
+            .map(|matchup| {
+                matchup
+                    .into_iter()
+                    .map(|candidate| self.candidates[candidate as usize])
+                    .collect()
+            })
+    }
+
+    /// Rank the whole network on the global results and the refinement results together.
+    /// Coverage is measured against the global plan, so it exceeds 1 once the plan is complete.
+    pub fn merge(
+        &self,
+        ranker: &impl Ranker,
+        global_results: &[MatchupResult],
+        refinement_results: &[MatchupResult],
+    ) -> Result<InterimRanking, SnicError> {
+        let results = global_results
+            .iter()
+            .chain(refinement_results)
+            .cloned()
+            .collect::<Vec<MatchupResult>>();
+        rank::rank_interim(self.network_size, self.decomposition.base, &results, ranker)
+    }
+}
+
+#[cfg(test)]
+mod test_top_k_refinement {
+    use super::TopKRefinement;
+    use crate::common_types::InputInt;
+    use crate::error::SnicError;
+    use crate::gber::Decomposition;
+    use crate::network::bootstrap::{self, BootstrapConfig, BootstrapOutcome};
+    use crate::network::comparison::MatchupResult;
+    use crate::network::matchup::LocalMatchupsManager;
+    use crate::network::rank::{self, BradleyTerryRanker};
+    use crate::network::test_helpers::ranked;
+    use std::collections::HashSet;
+
+
+    fn global_results() -> Vec<MatchupResult> {
+        LocalMatchupsManager::new(Decomposition::new(81, 3).unwrap())
+            .unwrap()
+            .map(ranked)
+            .collect()
+    }
+
+    fn current(results: &[MatchupResult]) -> BootstrapOutcome {
+        let config = BootstrapConfig {
+            resamples: 40,
+            ..Default::default()
+        };
+        bootstrap::bootstrap_ranking_from(&BradleyTerryRanker::default(), results, &config).unwrap()
+    }
+
+    #[test]
+    fn select_plausible_candidates() {
+        let results = global_results();
+        let current = current(&results);
+        let refinement = TopKRefinement::new(&current, 81, 3, 5).unwrap();
+        let candidates = &refinement.candidates;
+        assert!(candidates.len() > 5 && candidates.len() < 81);
+        let point_top = rank::order_by_scores(&current.point.scores, true);
+        assert_eq!(candidates[..5], point_top[..5]);
+        for &candidate in &candidates[5..] {
+            assert!(current.rank_intervals[candidate as usize].0 < 5, "{candidate}");
+        }
+    }
+
+    #[test]
+    fn play_only_among_candidates() {
+        let results = global_results();
+        let refinement = TopKRefinement::new(&current(&results), 81, 3, 5).unwrap();
+        let candidates = refinement.candidates.iter().copied().collect::<HashSet<InputInt>>();
+        let matchups = refinement.matchups().collect::<Vec<Vec<InputInt>>>();
+        assert!(!matchups.is_empty());
+        assert!(matchups.iter().flatten().all(|item| candidates.contains(item)));
+        assert!(matchups.iter().all(|matchup| matchup.len() == 3));
+
+        let refinement_results = matchups.into_iter().map(ranked).collect::<Vec<_>>();
+        let merged = refinement
+            .merge(&BradleyTerryRanker::default(), &results, &refinement_results)
+            .unwrap();
+        assert_eq!(merged.standings.len(), 81);
+        assert_eq!(merged.standings[0].item, 0);
+        assert!(merged.standings[..5].iter().all(|standing| candidates.contains(&standing.item)));
+        assert!(merged.coverage() > 1.0);
+    }
+
+    #[test]
+    fn fill_up_to_a_matchup() {
+        let results = vec![ranked(vec![0, 1, 2]); 20];
+        let refinement = TopKRefinement::new(&current(&results), 9, 3, 1).unwrap();
+        assert_eq!(refinement.candidates, vec![0, 1, 2]);
+        assert_eq!(refinement.matchups().count(), 1);
+    }
+
+    #[test]
+    fn reject_empty_top_and_small_networks() {
+        let results = global_results();
+        assert!(matches!(
+            TopKRefinement::new(&current(&results), 81, 3, 0),
+            Err(SnicError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            TopKRefinement::new(&current(&results), 40, 3, 5),
+            Err(SnicError::InvalidParameter(_))
+        ));
+    }
+}
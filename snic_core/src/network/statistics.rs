@@ -0,0 +1,146 @@
+//! Size of a matchup plan, derived without generating its matchups.
+use crate::common_types::{BaseInt, InputInt};
+use crate::error::SnicError;
+use crate::gber::Decomposition;
+use crate::network::matchup::{
+    calculate_comparisons_number_for, calculate_matchups_number_for, LocalMatchupsManager,
+};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanStatistics {
+    pub decomposition: Decomposition,
+    pub subnetwork_sizes: Vec<InputInt>,
+    /// Matchups of each subnetwork, in the order of the sizes.
+    pub subnetwork_matchups: Vec<u64>,
+    /// Inter-subnetwork and remainder matchups.
+    pub intersubnetwork_matchups: u64,
+    pub total_matchups: u64,
+    pub total_comparisons: u64,
+    /// Histogram of the items by the number of matchups they take part in:
+    /// (matchups, items) pairs, ascending by matchups.
+    /// Use `LocalMatchupsManager::matchups_containing` for the matchups of one item.
+    pub participation_histogram: Vec<(u64, InputInt)>,
+}
+
+impl PlanStatistics {
+    pub fn fewest_matchups_per_item(&self) -> u64 {
+        self.participation_histogram.first().map_or(0, |&(matchups, _)| matchups)
+    }
+
+    pub fn most_matchups_per_item(&self) -> u64 {
+        self.participation_histogram.last().map_or(0, |&(matchups, _)| matchups)
+    }
+}
+
+/// Statistics of the plan `LocalMatchupsManager` yields for a network.
+/// Only the inter-subnetwork matchups are built, the rest is counted.
+pub fn calculate_plan_statistics_for(
+    network_size: InputInt,
+    base: BaseInt,
+) -> Result<PlanStatistics, SnicError> {
+    let decomposition = Decomposition::new(network_size, base)?;
+    let manager = LocalMatchupsManager::new(decomposition.clone())?;
+    let subnetwork_sizes = decomposition.stream_all_components().collect::<Vec<InputInt>>();
+    let subnetwork_matchups = subnetwork_sizes
+        .iter()
+        .map(|&size| calculate_matchups_number_for(size, base))
+        .collect::<Result<Vec<u64>, SnicError>>()?;
+    let intersubnetwork_matchups = manager.intersubnetwork_matchups.len() as u64;
+    // The manager checked that its plan can be counted
+    let total_matchups = manager.total_matchups();
+
+    // Every item of a subnetwork appears once per level, remainder items never do
+    let mut participation = BTreeMap::<u64, InputInt>::new();
+    for (&size, &levels) in subnetwork_sizes.iter().zip(&decomposition.component_powers) {
+        *participation.entry(levels as u64).or_default() += size;
+    }
+    if decomposition.remainder > 0 {
+        *participation.entry(0).or_default() += decomposition.remainder as InputInt;
+    }
+    // Then the seats of the inter-subnetwork matchups move items up
+    let mut seats = HashMap::<InputInt, u64>::new();
+    for &item in manager.intersubnetwork_matchups.iter().flatten() {
+        *seats.entry(item).or_default() += 1;
+    }
+    for (item, item_seats) in seats {
+        let levels = subnetwork_levels_of(&decomposition, &subnetwork_sizes, item);
+        *participation.entry(levels).or_default() -= 1;
+        *participation.entry(levels + item_seats).or_default() += 1;
+    }
+    participation.retain(|_, items| *items > 0);
+
+    Ok(PlanStatistics {
+        decomposition,
+        subnetwork_sizes,
+        subnetwork_matchups,
+        intersubnetwork_matchups,
+        total_matchups,
+        total_comparisons: calculate_comparisons_number_for(total_matchups, base)?,
+        participation_histogram: participation.into_iter().collect(),
+    })
+}
+
+/// Levels of the subnetwork holding an item, 0 for the remainder.
+fn subnetwork_levels_of(
+    decomposition: &Decomposition,
+    subnetwork_sizes: &[InputInt],
+    item: InputInt,
+) -> u64 {
+    let mut offset: InputInt = 0;
+    for (&size, &levels) in subnetwork_sizes.iter().zip(&decomposition.component_powers) {
+        if item - offset < size {
+            return levels as u64;
+        }
+        offset += size;
+    }
+    0
+}
+
+#[cfg(test)]
+mod test_plan_statistics {
+    use super::calculate_plan_statistics_for;
+    use crate::common_types::{BaseInt, InputInt};
+    use crate::gber::Decomposition;
+    use crate::network::comparison;
+    use crate::network::matchup::LocalMatchupsManager;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn match_generated_plan() {
+        let cases: [(InputInt, BaseInt); 6] = [(1003, 5), (100, 3), (21, 10), (7, 2), (2, 2), (81, 3)];
+        for (network_size, base) in cases {
+            let statistics = calculate_plan_statistics_for(network_size, base).unwrap();
+            let plan = LocalMatchupsManager::new(Decomposition::new(network_size, base).unwrap())
+                .unwrap()
+                .collect::<Vec<Vec<InputInt>>>();
+            assert_eq!(statistics.total_matchups, plan.len() as u64);
+            assert_eq!(
+                statistics.total_comparisons,
+                comparison::expand_ranked_matchups(&plan).unwrap().len() as u64
+            );
+
+            let mut appearances = vec![0; network_size as usize];
+            for &item in plan.iter().flatten() {
+                appearances[item as usize] += 1;
+            }
+            let mut participation = BTreeMap::<u64, InputInt>::new();
+            for matchups in appearances {
+                *participation.entry(matchups).or_default() += 1;
+            }
+            assert_eq!(
+                statistics.participation_histogram,
+                participation.into_iter().collect::<Vec<_>>(),
+                "{network_size}/{base}"
+            );
+        }
+    }
+
+    #[test]
+    fn bound_participation() {
+        let statistics = calculate_plan_statistics_for(100, 3).unwrap();
+        assert_eq!(statistics.subnetwork_sizes, vec![81, 9, 9]);
+        assert_eq!(statistics.fewest_matchups_per_item(), 1);
+        assert!(statistics.most_matchups_per_item() >= 4);
+    }
+}
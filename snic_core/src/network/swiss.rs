@@ -0,0 +1,181 @@
+//! Swiss-style rounds to keep ranking once the fixed plan is exhausted.
+//! Every round groups items of similar current scores into matchups of `base` items,
+//! avoiding pairs that were already compared whenever a fresh item is left.
+use crate::common_types::{BaseInt, InputInt};
+use crate::error::SnicError;
+use crate::network::comparison::{self, pair_of, MatchupResult};
+use crate::network::rank::{self, Ranker};
+use std::collections::HashSet;
+
+/// Matchups of the next round, each item playing once.
+/// Groups are filled down the current ranking, strongest first:
+/// the first item not yet placed is joined by the next items it has not met,
+/// nor any other item of the group.
+/// When no fresh item is left, the nearest ones fill in and a pair repeats.
+/// The items left over at the end are topped up with the nearest items already placed,
+/// which then play twice in the round.
+pub fn propose_swiss_round<R: Ranker>(
+    ranker: &R,
+    network_size: InputInt,
+    base: BaseInt,
+    matchup_results: &[MatchupResult],
+) -> Result<Vec<Vec<InputInt>>, SnicError> {
+    // Also rejects bases and networks that cannot hold a matchup
+    let ranking = rank::rank_interim(network_size, base, matchup_results, ranker)?;
+    let order = ranking
+        .standings
+        .iter()
+        .map(|standing| standing.item)
+        .collect::<Vec<InputInt>>();
+    let compared = comparison::compared_pairs(matchup_results);
+    let group_size = base as usize;
+    // Unplaced positions in ranking order, linked in a ring through a sentinel,
+    // so every group walks past the items still unplaced only
+    let sentinel = order.len();
+    let mut next = (1..=sentinel).chain([0]).collect::<Vec<usize>>();
+    let mut previous = [sentinel].into_iter().chain(0..sentinel).collect::<Vec<usize>>();
+    let mut placed = vec![false; order.len()];
+    let mut unplaced = order.len();
+    let mut matchups = vec![];
+    while unplaced >= group_size {
+        let first_unplaced = next[sentinel];
+        let later_unplaced = || {
+            std::iter::successors(Some(next[first_unplaced]), |&position| Some(next[position]))
+                .take_while(|&position| position != sentinel)
+        };
+        let mut group = vec![first_unplaced];
+        for position in later_unplaced() {
+            if group.len() == group_size {
+                break;
+            }
+            let fresh = group
+                .iter()
+                .all(|&member| !compared.contains(&pair_of(order[member], order[position])));
+            if fresh {
+                group.push(position);
+            }
+        }
+        for position in later_unplaced() {
+            if group.len() == group_size {
+                break;
+            }
+            if !group.contains(&position) {
+                group.push(position);
+            }
+        }
+        group.sort_unstable();
+        for &position in &group {
+            placed[position] = true;
+            next[previous[position]] = next[position];
+            previous[next[position]] = previous[position];
+        }
+        unplaced -= group.len();
+        matchups.push(group);
+    }
+    if unplaced > 0 {
+        let mut group = (0..order.len())
+            .filter(|&position| !placed[position])
+            .collect::<Vec<usize>>();
+        let first_leftover = group[0];
+        let mut nearest = (0..order.len())
+            .filter(|&position| placed[position])
+            .collect::<Vec<usize>>();
+        nearest.sort_by_key(|&position| (position.abs_diff(first_leftover), position));
+        group.extend(nearest.into_iter().take(group_size - unplaced));
+        group.sort_unstable();
+        matchups.push(group);
+    }
+    Ok(matchups
+        .into_iter()
+        .map(|group| group.into_iter().map(|position| order[position]).collect())
+        .collect())
+}
+
+/// Pairs of the proposed matchups already compared in the results.
+pub fn count_repeated_pairs(matchups: &[Vec<InputInt>], matchup_results: &[MatchupResult]) -> usize {
+    let compared = comparison::compared_pairs(matchup_results);
+    matchups
+        .iter()
+        .flat_map(|matchup| {
+            matchup.iter().enumerate().flat_map(move |(position, &item)| {
+                matchup[position + 1..].iter().map(move |&peer| pair_of(item, peer))
+            })
+        })
+        .collect::<HashSet<_>>()
+        .intersection(&compared)
+        .count()
+}
+
+#[cfg(test)]
+mod test_swiss {
+    use super::{count_repeated_pairs, propose_swiss_round};
+    use crate::common_types::InputInt;
+    use crate::network::rank::BradleyTerryRanker;
+    use crate::network::test_helpers::ranked_all;
+
+
+    #[test]
+    fn every_item_plays_once() {
+        let ranker = BradleyTerryRanker::default();
+        let matchups = propose_swiss_round(&ranker, 27, 3, &[]).unwrap();
+        assert_eq!(matchups.len(), 9);
+        let mut items = matchups.iter().flatten().copied().collect::<Vec<InputInt>>();
+        items.sort();
+        assert!(items.into_iter().eq(0..27));
+    }
+
+    #[test]
+    fn avoid_pairs_round_after_round() {
+        let ranker = BradleyTerryRanker::default();
+        let mut results = vec![];
+        for round in 0..3 {
+            let matchups = propose_swiss_round(&ranker, 27, 3, &results).unwrap();
+            assert_eq!(count_repeated_pairs(&matchups, &results), 0, "round {round}");
+            results.extend(ranked_all(&matchups));
+        }
+    }
+
+    #[test]
+    fn group_similar_scores() {
+        let ranker = BradleyTerryRanker::default();
+        let first = propose_swiss_round(&ranker, 9, 3, &[]).unwrap();
+        assert_eq!(first, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8]]);
+        // The winners meet, then the runners-up, then the last ones
+        let second = propose_swiss_round(&ranker, 9, 3, &ranked_all(&first)).unwrap();
+        let mut groups = second
+            .into_iter()
+            .map(|mut matchup| {
+                matchup.sort();
+                matchup
+            })
+            .collect::<Vec<_>>();
+        groups.sort();
+        assert_eq!(groups, vec![vec![0, 3, 6], vec![1, 4, 7], vec![2, 5, 8]]);
+    }
+
+    #[test]
+    fn top_up_the_leftovers() {
+        let ranker = BradleyTerryRanker::default();
+        let matchups = propose_swiss_round(&ranker, 10, 3, &[]).unwrap();
+        assert_eq!(matchups.len(), 4);
+        assert!(matchups.iter().all(|matchup| matchup.len() == 3));
+        assert_eq!(matchups.last(), Some(&vec![7, 8, 9]));
+    }
+
+    #[test]
+    fn top_up_leftovers_skipped_high_in_the_ranking() {
+        // 1 already met 0, so the first group skips it and it is left over
+        let ranker = BradleyTerryRanker::default();
+        let matchups = propose_swiss_round(&ranker, 4, 3, &ranked_all(&[vec![0, 1]])).unwrap();
+        assert_eq!(matchups, vec![vec![0, 2, 3], vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn repeat_pairs_only_when_forced() {
+        let ranker = BradleyTerryRanker::default();
+        let results = ranked_all(&[vec![0, 1, 2]]);
+        let matchups = propose_swiss_round(&ranker, 3, 3, &results).unwrap();
+        assert_eq!(matchups, vec![vec![0, 1, 2]]);
+        assert_eq!(count_repeated_pairs(&matchups, &results), 3);
+    }
+}
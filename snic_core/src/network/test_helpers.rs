@@ -0,0 +1,31 @@
+//! Fixtures shared by the tests of the network modules.
+//! Orderings run from the best item to the worst,
+//! and simulated judges always rank lower items above higher ones.
+use crate::common_types::InputInt;
+use crate::network::comparison::{Comparison, MatchupResult};
+
+/// Items of the matchup as a judge orders them, lower items first.
+pub(crate) fn ranked_order(mut matchup: Vec<InputInt>) -> Vec<InputInt> {
+    matchup.sort_unstable();
+    matchup
+}
+
+/// Result of the matchup as a judge gives it, lower items winning.
+pub(crate) fn ranked(matchup: Vec<InputInt>) -> MatchupResult {
+    MatchupResult::from(ranked_order(matchup))
+}
+
+/// Results of every matchup, lower items winning.
+pub(crate) fn ranked_all(matchups: &[Vec<InputInt>]) -> Vec<MatchupResult> {
+    matchups.iter().cloned().map(ranked).collect()
+}
+
+/// Results of orderings taken as they are given.
+pub(crate) fn strict_results(orderings: &[Vec<InputInt>]) -> Vec<MatchupResult> {
+    orderings.iter().cloned().map(MatchupResult::from).collect()
+}
+
+/// Decisive comparisons of (winner, loser) pairs.
+pub(crate) fn strict_comparisons(pairs: &[(InputInt, InputInt)]) -> Vec<Comparison> {
+    pairs.iter().copied().map(Comparison::from).collect()
+}
@@ -0,0 +1,386 @@
+//! Session over a matchup plan: hands out matchups and collects their results.
+//! Matchups are identified by their position in the plan,
+//! followed by the matchups added after it,
+//! so an id stays valid across expiry and reissue.
+use crate::common_types::InputInt;
+use crate::error::SnicError;
+use crate::network::adaptive::{self, AdaptiveConfig};
+use crate::network::comparison::{self, Comparison, MatchupResult};
+use crate::network::matchup::LocalMatchupsManager;
+use crate::network::rank::{self, InterimRanking, Ranker};
+use crate::network::swiss;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Position of a matchup in the plan of its tournament.
+pub type MatchupId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchupStatus {
+    /// Handed out and awaiting its result.
+    Pending,
+    /// Handed out, but given up on; it is handed out again before new matchups.
+    Expired,
+    Completed,
+}
+
+#[derive(Debug)]
+pub struct Tournament {
+    plan: LocalMatchupsManager,
+    /// Matchups added beyond the plan, handed out once it is exhausted.
+    extra: VecDeque<Vec<InputInt>>,
+    issued: u64,
+    pending: BTreeMap<MatchupId, Vec<InputInt>>,
+    expired: BTreeMap<MatchupId, Vec<InputInt>>,
+    /// Results in the order they were recorded.
+    results: Vec<MatchupResult>,
+    /// Position of the result of every completed matchup.
+    completed: BTreeMap<MatchupId, usize>,
+}
+
+impl Tournament {
+    /// Start a tournament over a plan that has not been iterated yet.
+    /// Rejects plans that already yielded matchups, since ids count from the start.
+    pub fn new(plan: LocalMatchupsManager) -> Result<Self, SnicError> {
+        let planned = plan.total_matchups();
+        let remaining = plan.size_hint().0 as u64;
+        if remaining != planned {
+            return Err(SnicError::InvalidParameter(format!(
+                "the plan already yielded {} of its {planned} matchups",
+                planned.saturating_sub(remaining)
+            )));
+        }
+        Ok(Self {
+            plan,
+            extra: VecDeque::new(),
+            issued: 0,
+            pending: BTreeMap::new(),
+            expired: BTreeMap::new(),
+            results: vec![],
+            completed: BTreeMap::new(),
+        })
+    }
+
+    pub fn network_size(&self) -> InputInt {
+        self.plan.network_size()
+    }
+
+    /// Hand out the next matchup: expired ones first, lowest id first,
+    /// then the plan, then the matchups added beyond it.
+    pub fn next_matchup(&mut self) -> Option<(MatchupId, Vec<InputInt>)> {
+        let (id, matchup) = match self.expired.pop_first() {
+            Some(reissued) => reissued,
+            None => {
+                let matchup = self.plan.next().or_else(|| self.extra.pop_front())?;
+                self.issued += 1;
+                (self.issued - 1, matchup)
+            }
+        };
+        self.pending.insert(id, matchup.clone());
+        Some((id, matchup))
+    }
+
+    /// Record the result of a matchup handed out before.
+    /// Late results for expired matchups are accepted too.
+    /// The result must rank exactly the items of the matchup.
+    pub fn record(&mut self, id: MatchupId, result: impl Into<MatchupResult>) -> Result<(), SnicError> {
+        let result = result.into();
+        let matchup = match self.status(id) {
+            Some(MatchupStatus::Pending) => &self.pending[&id],
+            Some(MatchupStatus::Expired) => &self.expired[&id],
+            Some(MatchupStatus::Completed) => {
+                return Err(SnicError::InvalidResult(format!(
+                    "matchup {id} already has a result"
+                )))
+            }
+            None => {
+                return Err(SnicError::InvalidResult(format!(
+                    "matchup {id} was never handed out"
+                )))
+            }
+        };
+        result.validate(id as usize)?;
+        let mut expected = matchup.clone();
+        let mut ranked = result.items().collect::<Vec<InputInt>>();
+        expected.sort_unstable();
+        ranked.sort_unstable();
+        if expected != ranked {
+            return Err(SnicError::InvalidResult(format!(
+                "the result of matchup {id} ranks {ranked:?} instead of {expected:?}"
+            )));
+        }
+        self.pending.remove(&id);
+        self.expired.remove(&id);
+        self.completed.insert(id, self.results.len());
+        self.results.push(result);
+        Ok(())
+    }
+
+    /// Queue matchups beyond the plan, each of distinct items of the network.
+    pub fn extend(&mut self, matchups: impl IntoIterator<Item = Vec<InputInt>>) -> Result<(), SnicError> {
+        let network_size = self.network_size();
+        let matchups = matchups.into_iter().collect::<Vec<_>>();
+        for (position, matchup) in matchups.iter().enumerate() {
+            comparison::check_distinct_items(position, matchup)?;
+            if matchup.len() < 2 || matchup.iter().any(|&item| item >= network_size) {
+                return Err(SnicError::InvalidParameter(format!(
+                    "matchup {matchup:?} needs two or more items of the {network_size} in the network"
+                )));
+            }
+        }
+        self.extra.extend(matchups);
+        Ok(())
+    }
+
+    /// Queue the matchups `propose_adaptive_matchups` picks from the results so far.
+    /// Returns how many were added.
+    pub fn extend_adaptively(
+        &mut self,
+        ranker: &impl Ranker,
+        config: &AdaptiveConfig,
+    ) -> Result<usize, SnicError> {
+        let matchups = adaptive::propose_adaptive_matchups(
+            ranker,
+            self.network_size(),
+            self.plan.network_gber.base,
+            &self.results,
+            config,
+        )?;
+        let added = matchups.len();
+        self.extend(matchups)?;
+        Ok(added)
+    }
+
+    /// Queue the next round of `propose_swiss_round` over the results so far.
+    /// Returns how many matchups were added.
+    pub fn extend_swiss_round(&mut self, ranker: &impl Ranker) -> Result<usize, SnicError> {
+        let matchups = swiss::propose_swiss_round(
+            ranker,
+            self.network_size(),
+            self.plan.network_gber.base,
+            &self.results,
+        )?;
+        let added = matchups.len();
+        self.extend(matchups)?;
+        Ok(added)
+    }
+
+    /// Give up on a pending matchup, so that it is handed out again.
+    pub fn expire(&mut self, id: MatchupId) -> Result<(), SnicError> {
+        let matchup = self.pending.remove(&id).ok_or_else(|| {
+            SnicError::InvalidResult(format!("matchup {id} is not pending"))
+        })?;
+        self.expired.insert(id, matchup);
+        Ok(())
+    }
+
+    pub fn status(&self, id: MatchupId) -> Option<MatchupStatus> {
+        if self.completed.contains_key(&id) {
+            Some(MatchupStatus::Completed)
+        } else if self.pending.contains_key(&id) {
+            Some(MatchupStatus::Pending)
+        } else if self.expired.contains_key(&id) {
+            Some(MatchupStatus::Expired)
+        } else {
+            None
+        }
+    }
+
+    /// Ids of the matchups awaiting their result, ascending.
+    pub fn pending(&self) -> impl Iterator<Item = MatchupId> + '_ {
+        self.pending.keys().copied()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn completed_count(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Matchups never handed out, from the plan and beyond, expired ones not included.
+    pub fn remaining_count(&self) -> usize {
+        self.plan.size_hint().0 + self.extra.len()
+    }
+
+    /// Every matchup handed out or queued has its result.
+    pub fn is_finished(&self) -> bool {
+        self.remaining_count() == 0 && self.pending.is_empty() && self.expired.is_empty()
+    }
+
+    /// Recorded results by matchup id, ascending.
+    pub fn results(&self) -> impl Iterator<Item = (MatchupId, &MatchupResult)> + '_ {
+        self.completed
+            .iter()
+            .map(|(&id, &position)| (id, &self.results[position]))
+    }
+
+    pub fn comparisons(&self) -> Vec<Comparison> {
+        self.results.iter().flat_map(MatchupResult::to_comparisons).collect()
+    }
+
+    /// Interim ranking of every item of the network, with the coverage of the plan.
+    pub fn ranking(&self, ranker: &impl Ranker) -> Result<InterimRanking, SnicError> {
+        rank::rank_interim(
+            self.network_size(),
+            self.plan.network_gber.base,
+            &self.results,
+            ranker,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_tournament {
+    use super::{MatchupStatus, Tournament};
+    use crate::common_types::InputInt;
+    use crate::error::SnicError;
+    use crate::gber::Decomposition;
+    use crate::network::adaptive::AdaptiveConfig;
+    use crate::network::bootstrap::BootstrapConfig;
+    use crate::network::comparison::MatchupResult;
+    use crate::network::matchup::LocalMatchupsManager;
+    use crate::network::rank::BradleyTerryRanker;
+    use crate::network::test_helpers::{ranked, ranked_order};
+
+    fn tournament(network_size: InputInt, base: u16) -> Tournament {
+        Tournament::new(
+            LocalMatchupsManager::new(Decomposition::new(network_size, base).unwrap()).unwrap(),
+        )
+        .unwrap()
+    }
+
+
+    #[test]
+    fn play_the_whole_plan() {
+        let mut session = tournament(27, 3);
+        let planned = session.remaining_count();
+        while let Some((id, matchup)) = session.next_matchup() {
+            session.record(id, ranked(matchup)).unwrap();
+        }
+        assert!(session.is_finished());
+        assert_eq!(session.completed_count(), planned);
+        let ranking = session.ranking(&BradleyTerryRanker::default()).unwrap();
+        assert_eq!(ranking.coverage(), 1.0);
+        let standings = ranking.standings;
+        assert_eq!(standings.len(), 27);
+        assert_eq!(standings.first().map(|standing| standing.item), Some(0));
+        assert_eq!(standings.last().map(|standing| standing.item), Some(26));
+    }
+
+    #[test]
+    fn reject_unknown_and_duplicate_results() {
+        let mut session = tournament(9, 3);
+        let (id, matchup) = session.next_matchup().unwrap();
+        assert!(matches!(
+            session.record(id + 1, ranked(matchup.clone())),
+            Err(SnicError::InvalidResult(_))
+        ));
+        session.record(id, ranked(matchup.clone())).unwrap();
+        assert!(matches!(
+            session.record(id, ranked(matchup)),
+            Err(SnicError::InvalidResult(_))
+        ));
+        assert_eq!(session.completed_count(), 1);
+    }
+
+    #[test]
+    fn reject_results_for_other_items() {
+        let mut session = tournament(9, 3);
+        let (id, mut matchup) = session.next_matchup().unwrap();
+        let outsider = (0..9).find(|item| !matchup.contains(item)).unwrap();
+        matchup[0] = outsider;
+        assert!(matches!(
+            session.record(id, ranked(matchup)),
+            Err(SnicError::InvalidResult(_))
+        ));
+        assert_eq!(session.status(id), Some(MatchupStatus::Pending));
+        assert!(session.record(id, MatchupResult::default()).is_err());
+    }
+
+    #[test]
+    fn reissue_expired_matchups() {
+        let mut session = tournament(9, 3);
+        let (first, first_matchup) = session.next_matchup().unwrap();
+        let (second, _) = session.next_matchup().unwrap();
+        session.expire(first).unwrap();
+        assert_eq!(session.status(first), Some(MatchupStatus::Expired));
+        assert!(session.expire(first).is_err());
+        assert_eq!(session.next_matchup(), Some((first, first_matchup.clone())));
+        assert_eq!(session.pending().collect::<Vec<_>>(), vec![first, second]);
+
+        // A late result still counts once the matchup expired
+        session.expire(second).unwrap();
+        let (third, _) = session.next_matchup().unwrap();
+        assert_eq!(third, second);
+        session.expire(third).unwrap();
+        session.record(first, ranked(first_matchup)).unwrap();
+        assert_eq!(session.status(first), Some(MatchupStatus::Completed));
+    }
+
+    #[test]
+    fn continue_past_the_plan() {
+        let mut session = tournament(9, 3);
+        while let Some((id, matchup)) = session.next_matchup() {
+            session.record(id, ranked(matchup)).unwrap();
+        }
+        let planned = session.completed_count() as u64;
+        assert!(session.extend([vec![0, 9]]).is_err());
+        assert!(session.extend([vec![0, 0, 1]]).is_err());
+        let config = AdaptiveConfig::new(2).with_bootstrap(BootstrapConfig {
+            resamples: 10,
+            ..Default::default()
+        });
+        assert_eq!(session.extend_adaptively(&BradleyTerryRanker::default(), &config), Ok(2));
+        assert!(!session.is_finished());
+        let (id, matchup) = session.next_matchup().unwrap();
+        assert_eq!((id, matchup.len()), (planned, 3));
+        session.record(id, ranked(matchup)).unwrap();
+        assert_eq!(session.remaining_count(), 1);
+        let ranking = session.ranking(&BradleyTerryRanker::default()).unwrap();
+        assert!(ranking.coverage() > 1.0);
+    }
+
+    #[test]
+    fn play_swiss_rounds_after_the_plan() {
+        let mut session = tournament(9, 3);
+        for round in 0..3 {
+            if round > 0 {
+                assert_eq!(session.extend_swiss_round(&BradleyTerryRanker::default()), Ok(3));
+            }
+            while let Some((id, matchup)) = session.next_matchup() {
+                session.record(id, ranked(matchup)).unwrap();
+            }
+        }
+        assert!(session.is_finished());
+        let ranking = session.ranking(&BradleyTerryRanker::default()).unwrap();
+        assert_eq!(ranking.standings[0].item, 0);
+    }
+
+    #[test]
+    fn interim_ranking_covers_the_network() {
+        let mut session = tournament(27, 3);
+        let (id, matchup) = session.next_matchup().unwrap();
+        session.record(id, ranked(matchup.clone())).unwrap();
+        let ranking = session.ranking(&BradleyTerryRanker::default()).unwrap();
+        assert_eq!(ranking.standings.len(), 27);
+        assert_eq!(ranking.never_compared().len(), 24);
+        let leaders = ranking.standings[..3]
+            .iter()
+            .map(|standing| standing.item)
+            .collect::<Vec<InputInt>>();
+        assert_eq!(leaders, ranked_order(matchup));
+        assert_eq!(ranking.observed_comparisons, 3);
+        assert!(ranking.coverage() > 0.0 && ranking.coverage() < 0.1);
+    }
+
+    #[test]
+    fn reject_plans_already_iterated() {
+        let mut plan = LocalMatchupsManager::new(Decomposition::new(27, 3).unwrap()).unwrap();
+        plan.next();
+        assert!(matches!(
+            Tournament::new(plan),
+            Err(SnicError::InvalidParameter(_))
+        ));
+    }
+}
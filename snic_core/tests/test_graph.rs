@@ -9,9 +9,7 @@ fn simple_rank_preserves_sorting() {
     let base: BaseInt = u8::MAX.into();
     let length: InputInt = u8::MAX.into();
     let gber = Decomposition::new(length, base).unwrap();
-    let network_matchups = matchup::LocalMatchupsManager::new(gber);
-    let matchups = network_matchups.subnetwork_iterators
-        .into_iter().flatten().chain(network_matchups.intersubnetwork_matchups)
+    let matchups = matchup::LocalMatchupsManager::new(gber).unwrap()
         .collect::<Vec<Vec<InputInt>>>();
     let mould = comparison::generate_expansion_mould_for(base);
     let pairwise_comparisons = matchups.into_iter()
@@ -21,3 +19,26 @@ fn simple_rank_preserves_sorting() {
     sorted_rating.sort_by(|a, b| a.partial_cmp(b).unwrap());
     assert_eq!(sorted_rating, rating);
 }
+
+#[test]
+fn rankers_respect_comparisons() {
+    let base: BaseInt = 4;
+    let length: InputInt = 300;
+    let gber = Decomposition::new(length, base).unwrap();
+    let matchups = matchup::LocalMatchupsManager::new(gber).unwrap()
+        .collect::<Vec<Vec<InputInt>>>();
+    let pairwise_comparisons = comparison::expand_ranked_matchups(&matchups).unwrap();
+    let rankers: [&dyn rank::Ranker; 2] = [
+        &rank::PageRankRanker::default(),
+        &rank::BradleyTerryRanker::default(),
+    ];
+    for ranker in rankers {
+        let outcome = ranker.rank(&pairwise_comparisons);
+        assert!(outcome.converged);
+        assert_eq!(outcome.scores.len(), length as usize);
+        let respected_comparisons = pairwise_comparisons.iter()
+            .filter(|c| outcome.scores[c.winner as usize] > outcome.scores[c.loser as usize])
+            .count();
+        assert!(respected_comparisons as f64 >= 0.95 * pairwise_comparisons.len() as f64);
+    }
+}
@@ -1,57 +1,641 @@
 //! Python bindings for the snic library.
-use snic_core::{common_types, gber, network};
+// The pyo3 macros convert `PyResult` errors into themselves.
+#![allow(clippy::useless_conversion)]
+use snic_core::{common_types, error::SnicError, gber, network};
+use pyo3::exceptions::{PyIndexError, PyKeyError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyIterator};
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyArrayMethods, PyUntypedArrayMethods};
 
-/// Generate all matchups of a network of a given size.
-/// TODO: stream instead of collecting all in memory.
+/// Lazy stream over the matchups of a network.
+/// Holds only the position within each subnetwork
+/// and the few inter-subnetwork matchups,
+/// so memory grows with the number of subnetworks,
+/// logarithmic in the network size, and never with the items themselves.
+#[pyclass(module = "snic")]
+struct MatchupStream {
+    matchups_manager: network::matchup::LocalMatchupsManager,
+}
+
+#[pymethods]
+impl MatchupStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<Vec<common_types::InputInt>> {
+        slf.matchups_manager.next()
+    }
+
+    fn __length_hint__(&self) -> usize {
+        self.matchups_manager.size_hint().0
+    }
+
+    /// Up to `count` next matchups as a 2-D array, one row per matchup.
+    fn take_array<'py>(
+        &mut self,
+        py: Python<'py>,
+        count: usize,
+    ) -> PyResult<Bound<'py, PyArray2<common_types::InputInt>>> {
+        let matchup_size = self.matchups_manager.network_gber.base as usize;
+        let rows = count.min(self.matchups_manager.size_hint().0);
+        let mut items = Vec::with_capacity(rows * matchup_size);
+        for matchup in self.matchups_manager.by_ref().take(rows) {
+            items.extend(matchup);
+        }
+        let matchups = Array2::from_shape_vec((rows, matchup_size), items)
+            .map_err(|error| PyValueError::new_err(error.to_string()))?;
+        Ok(matchups.into_pyarray_bound(py))
+    }
+
+    /// Matchup at a position counted from the start of the stream.
+    fn matchup_at(&self, position: u64) -> Option<Vec<common_types::InputInt>> {
+        self.matchups_manager.matchup_at(position)
+    }
+
+    /// Stream positions of the matchups holding an item, ascending.
+    fn matchups_containing(&self, item: common_types::InputInt) -> Vec<u64> {
+        self.matchups_manager.matchups_containing(item)
+    }
+
+    /// JSON checkpoint of the stream, to pass to `resume_matches_from`.
+    fn cursor(&self) -> PyResult<String> {
+        serde_json::to_string(&self.matchups_manager.cursor())
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+}
+
+/// Stream all matchups of a network of a given size, one at a time.
+/// The ordering is one of "intra_first", "inter_first" or "interleaved".
+/// It defaults to "inter_first", the schedule the bindings always yielded,
+/// while the Rust `LocalMatchupsManager` defaults to "intra_first".
+/// A seed shuffles the items over the network positions, reproducibly.
 #[pyfunction]
-#[pyo3(signature = (network_size, match_size))]
+#[pyo3(signature = (network_size, match_size, ordering = "inter_first", seed = None))]
 fn stream_matches_from<'py>(
     _py: Python<'py>,
     network_size: common_types::InputInt,
     match_size: common_types::BaseInt,
-) -> Vec<Vec<common_types::InputInt>> {
+    ordering: &str,
+    seed: Option<u64>,
+) -> PyResult<MatchupStream> {
+    let ordering = ordering
+        .parse::<network::matchup::MatchupOrdering>()
+        .map_err(PyValueError::new_err)?;
     let decomposition =
-        gber::Decomposition::new(network_size, match_size).expect("Invalid decomposition values");
-    let matchups_manager = network::matchup::LocalMatchupsManager::new(decomposition);
-    matchups_manager.intersubnetwork_matchups
-        .into_iter().chain(
-            matchups_manager.subnetwork_iterators
-                .into_iter().flatten()
-        ).collect()
+        gber::Decomposition::new(network_size, match_size).map_err(to_value_error)?;
+    let mut matchups_manager = network::matchup::LocalMatchupsManager::new(decomposition)
+        .map_err(to_value_error)?
+        .with_ordering(ordering);
+    if let Some(seed) = seed {
+        matchups_manager = matchups_manager.with_seed(seed);
+    }
+    Ok(MatchupStream { matchups_manager })
 }
 
 
+/// Lazy stream over one shard of the matchups of a network.
+#[pyclass(module = "snic")]
+struct MatchupShardStream {
+    shard: network::matchup::MatchupShard,
+}
+
+#[pymethods]
+impl MatchupShardStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<Vec<common_types::InputInt>> {
+        slf.shard.next()
+    }
+
+    fn __length_hint__(&self) -> usize {
+        self.shard.size_hint().0
+    }
+}
+
+/// Stream one of `shard_count` disjoint, balanced shards of the matchups of a network.
+/// Together the shards hold every matchup of `stream_matches_from` exactly once.
 #[pyfunction]
-#[pyo3(signature = (ranked_matches))]
-fn stream_rankings_from<'py>(
-    _py: Python<'py>,
+#[pyo3(signature = (network_size, match_size, shard_index, shard_count, ordering = "inter_first", seed = None))]
+fn stream_shard_from<'py>(
+    py: Python<'py>,
+    network_size: common_types::InputInt,
+    match_size: common_types::BaseInt,
+    shard_index: usize,
+    shard_count: usize,
+    ordering: &str,
+    seed: Option<u64>,
+) -> PyResult<MatchupShardStream> {
+    let matchups = stream_matches_from(py, network_size, match_size, ordering, seed)?;
+    Ok(MatchupShardStream {
+        shard: matchups
+            .matchups_manager
+            .shard(shard_index, shard_count)
+            .map_err(to_value_error)?,
+    })
+}
+
+/// Resume a matchup stream right after the matchups yielded at its checkpoint.
+#[pyfunction]
+#[pyo3(signature = (cursor))]
+fn resume_matches_from(cursor: &str) -> PyResult<MatchupStream> {
+    let cursor = serde_json::from_str::<network::matchup::ManagerCursor>(cursor)
+        .map_err(|error| PyValueError::new_err(error.to_string()))?;
+    Ok(MatchupStream {
+        matchups_manager: network::matchup::LocalMatchupsManager::resume(&cursor)
+            .map_err(to_value_error)?,
+    })
+}
+
+/// Rank items by their ranked matchups, best item first in each matchup.
+/// Returns a `Ranking`, which iterates over the item indices in rank order.
+/// Scores come from PageRank and are higher for stronger items.
+/// `descending` lists the strongest item first, the default,
+/// and items never compared come last in either order.
+/// Optional `weights`, one positive value per matchup, make some matchups count more than others.
+/// Other Python threads keep running meanwhile.
+#[pyfunction]
+#[pyo3(signature = (ranked_matches, descending = true, weights = None))]
+fn stream_rankings_from(
+    py: Python<'_>,
     ranked_matches: Vec<Vec<common_types::InputInt>>,
-) -> Vec<common_types::InputInt> {
-    let expansion_mould =
-        network::comparison::generate_expansion_mould_for(
-            ranked_matches[0].len() as common_types::BaseInt
-    );
-    let pairwise_comparisons = ranked_matches.into_iter()
-        .flat_map(
-            |ranking| {
-                network::comparison::convert_to_comparisons(
-                    &ranking[..],
-                    &expansion_mould[..],
-                )
+    descending: bool,
+    weights: Option<Vec<f32>>,
+) -> PyResult<Ranking> {
+    let (outcome, tally) = rank_outcome_of(py, &ranked_matches, weights.as_deref())?;
+    Ranking::new(py, &outcome, &tally, descending)
+}
+
+/// PageRank outcome of the items, higher scores for stronger ones,
+/// with their comparison counts.
+/// Items never compared get a NaN score.
+fn rank_outcome_of<R: AsRef<[common_types::InputInt]> + Sync>(
+    py: Python<'_>,
+    ranked_matches: &[R],
+    weights: Option<&[f32]>,
+) -> PyResult<(network::rank::RankingOutcome, network::comparison::ComparisonTally)> {
+    py.allow_threads(|| {
+        let mut outcome =
+            network::rank::PageRankRanker::default().rank_matchups(ranked_matches, weights)?;
+        let tally = network::comparison::ComparisonTally::from_ranked_matchups(ranked_matches);
+        for (item, score) in outcome.scores.iter_mut().enumerate() {
+            if tally.comparisons.get(item).is_none_or(|&count| count == 0) {
+                *score = f32::NAN;
+            }
+        }
+        Ok((outcome, tally))
+    })
+    .map_err(to_value_error)
+}
+
+/// Scores, rank positions and comparison counts of every item,
+/// with the convergence of the PageRank run that scored them.
+/// The per-item arrays are indexed by item, `order` lists the items by rank.
+/// They are read-only NumPy arrays, built once and shared by every access.
+/// Iterating, indexing and `len` follow `order`, like the list returned before.
+#[pyclass(module = "snic", frozen, get_all)]
+struct Ranking {
+    order: Py<PyArray1<common_types::InputInt>>,
+    /// Higher for stronger items, NaN for items never compared.
+    scores: Py<PyArray1<f32>>,
+    ranks: Py<PyArray1<common_types::InputInt>>,
+    comparisons: Py<PyArray1<u64>>,
+    wins: Py<PyArray1<u64>>,
+    losses: Py<PyArray1<u64>>,
+    never_compared: Py<PyArray1<bool>>,
+    descending: bool,
+    /// Whether PageRank settled within its iteration budget.
+    converged: bool,
+    iterations: usize,
+}
+
+impl Ranking {
+    fn new(
+        py: Python<'_>,
+        outcome: &network::rank::RankingOutcome,
+        tally: &network::comparison::ComparisonTally,
+        descending: bool,
+    ) -> PyResult<Self> {
+        let standings = network::rank::standings_from(&outcome.scores, tally, descending);
+        let item_count = standings.len();
+        let mut order = Vec::with_capacity(item_count);
+        let mut scores = vec![f32::NAN; item_count];
+        let mut ranks = vec![0; item_count];
+        let mut comparisons = vec![0; item_count];
+        let mut wins = vec![0; item_count];
+        let mut losses = vec![0; item_count];
+        let mut never_compared = vec![true; item_count];
+        for standing in standings {
+            let item = standing.item as usize;
+            order.push(standing.item);
+            scores[item] = standing.score;
+            ranks[item] = standing.rank;
+            comparisons[item] = standing.comparisons;
+            wins[item] = standing.wins;
+            losses[item] = standing.losses;
+            never_compared[item] = standing.never_compared();
+        }
+        Ok(Ranking {
+            order: read_only_array(py, order)?,
+            scores: read_only_array(py, scores)?,
+            ranks: read_only_array(py, ranks)?,
+            comparisons: read_only_array(py, comparisons)?,
+            wins: read_only_array(py, wins)?,
+            losses: read_only_array(py, losses)?,
+            never_compared: read_only_array(py, never_compared)?,
+            descending,
+            converged: outcome.converged,
+            iterations: outcome.iterations,
+        })
+    }
+
+    fn item_count(&self, py: Python<'_>) -> usize {
+        PyUntypedArrayMethods::len(self.order.bind(py))
+    }
+}
+
+/// Hand a vector over to NumPy without copying it, and lock it against writes.
+fn read_only_array<T: numpy::Element>(
+    py: Python<'_>,
+    items: Vec<T>,
+) -> PyResult<Py<PyArray1<T>>> {
+    let array = items.into_pyarray_bound(py);
+    let flags = PyDict::new_bound(py);
+    flags.set_item("write", false)?;
+    array.call_method("setflags", (), Some(&flags))?;
+    Ok(array.unbind())
+}
+
+#[pymethods]
+impl Ranking {
+    fn __len__(&self, py: Python<'_>) -> usize {
+        self.item_count(py)
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        // Plain Python integers, like the list returned before
+        self.order.bind(py).call_method0("tolist")?.iter()
+    }
+
+    fn __getitem__(&self, py: Python<'_>, position: isize) -> PyResult<common_types::InputInt> {
+        let position = if position < 0 {
+            position + self.item_count(py) as isize
+        } else {
+            position
+        };
+        usize::try_from(position)
+            .ok()
+            .and_then(|position| self.order.bind(py).get_owned([position]))
+            .ok_or_else(|| PyIndexError::new_err("ranking position out of range"))
+    }
+
+    /// Score, rank, comparisons, wins and losses of one item.
+    fn standing<'py>(&self, py: Python<'py>, item: usize) -> PyResult<Bound<'py, PyDict>> {
+        if item >= self.item_count(py) {
+            return Err(PyIndexError::new_err(format!("no item {item} in the ranking")));
+        }
+        let standing = PyDict::new_bound(py);
+        standing.set_item("score", self.scores.bind(py).get_owned([item]))?;
+        standing.set_item("rank", self.ranks.bind(py).get_owned([item]))?;
+        standing.set_item("comparisons", self.comparisons.bind(py).get_owned([item]))?;
+        standing.set_item("wins", self.wins.bind(py).get_owned([item]))?;
+        standing.set_item("losses", self.losses.bind(py).get_owned([item]))?;
+        standing.set_item("never_compared", self.never_compared.bind(py).get_owned([item]))?;
+        Ok(standing)
+    }
+}
+
+/// Rank items by a 2-D array of ranked matchups, one row per matchup, best item first.
+/// Returns the same `Ranking` as `stream_rankings_from`.
+/// Any integer dtype is accepted, as long as every item is a valid index.
+/// The array is copied before ranking, so other Python threads may keep using it.
+/// Optional `weights` count like in `stream_rankings_from`, one per row.
+#[pyfunction]
+#[pyo3(signature = (ranked_matches, descending = true, weights = None))]
+fn rank_matches_array(
+    py: Python<'_>,
+    ranked_matches: &Bound<'_, PyAny>,
+    descending: bool,
+    weights: Option<Vec<f32>>,
+) -> PyResult<Ranking> {
+    let (items, matchup_size) = owned_matchups_of(ranked_matches)?;
+    if matchup_size == 0 {
+        return Err(to_value_error(SnicError::InvalidRankings(
+            "a matchup must rank at least two items".into(),
+        )));
+    }
+    let rows = items
+        .chunks_exact(matchup_size)
+        .collect::<Vec<&[common_types::InputInt]>>();
+    let (outcome, tally) = rank_outcome_of(py, &rows, weights.as_deref())?;
+    Ranking::new(py, &outcome, &tally, descending)
+}
+
+/// Items of a 2-D integer array in row order, with the length of its rows.
+fn owned_matchups_of(
+    ranked_matches: &Bound<'_, PyAny>,
+) -> PyResult<(Vec<common_types::InputInt>, usize)> {
+    macro_rules! copy_if_dtype {
+        ($($dtype:ty),*) => {
+            $(
+                if let Ok(array) = ranked_matches.downcast::<PyArray2<$dtype>>() {
+                    return copy_matchups(array);
+                }
+            )*
+        };
+    }
+    copy_if_dtype!(u8, u16, u32, u64, i8, i16, i32, i64);
+    Err(PyValueError::new_err(
+        "ranked matches must be a 2-D NumPy array of integers",
+    ))
+}
+
+fn copy_matchups<T>(
+    array: &Bound<'_, PyArray2<T>>,
+) -> PyResult<(Vec<common_types::InputInt>, usize)>
+where
+    T: numpy::Element + Copy + std::fmt::Display,
+    common_types::InputInt: TryFrom<T>,
+{
+    let array = array.try_readonly()?;
+    let matchups = array.as_array();
+    let items = matchups
+        .iter()
+        .map(|&item| {
+            common_types::InputInt::try_from(item).map_err(|_| {
+                PyValueError::new_err(format!("{item} is not a valid item index"))
+            })
+        })
+        .collect::<PyResult<Vec<common_types::InputInt>>>()?;
+    Ok((items, matchups.ncols()))
+}
+
+/// All matchups of a network as a 2-D array, one row per matchup.
+#[pyfunction]
+#[pyo3(signature = (network_size, match_size, ordering = "inter_first", seed = None))]
+fn matches_array_from<'py>(
+    py: Python<'py>,
+    network_size: common_types::InputInt,
+    match_size: common_types::BaseInt,
+    ordering: &str,
+    seed: Option<u64>,
+) -> PyResult<Bound<'py, PyArray2<common_types::InputInt>>> {
+    let mut matchups = stream_matches_from(py, network_size, match_size, ordering, seed)?;
+    matchups.take_array(py, usize::MAX)
+}
+
+/// Numbering of hashable Python objects as network items,
+/// in the order they are given.
+#[pyclass(module = "snic", frozen)]
+struct Catalog {
+    labels: Vec<PyObject>,
+    indices: Py<PyDict>,
+}
+
+#[pymethods]
+impl Catalog {
+    #[new]
+    fn new(labels: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let py = labels.py();
+        let indices = PyDict::new_bound(py);
+        let mut ordered_labels = vec![];
+        for label in labels.iter()? {
+            let label = label?;
+            if indices.contains(&label)? {
+                return Err(PyValueError::new_err(format!(
+                    "{} is listed more than once",
+                    label.repr()?
+                )));
             }
-        ).collect::<Vec<(common_types::InputInt, common_types::InputInt)>>();
-    let rank_scores = network::rank::get_ranking_from(pairwise_comparisons);
-    let mut indices = (0..(rank_scores.len() as common_types::InputInt))
-        .collect::<Vec<common_types::InputInt>>();
-    indices.sort_by(|a, b| rank_scores[*a as usize].partial_cmp(&rank_scores[*b as usize]).unwrap());
-    indices
+            let index = common_types::InputInt::try_from(ordered_labels.len())
+                .map_err(|_| to_value_error(SnicError::Overflow))?;
+            indices.set_item(&label, index)?;
+            ordered_labels.push(label.unbind());
+        }
+        Ok(Self {
+            labels: ordered_labels,
+            indices: indices.unbind(),
+        })
+    }
+
+    fn __len__(&self) -> usize {
+        self.labels.len()
+    }
+
+    fn index_of(&self, label: &Bound<'_, PyAny>) -> PyResult<common_types::InputInt> {
+        match self.indices.bind(label.py()).get_item(label)? {
+            Some(index) => index.extract(),
+            None => Err(PyKeyError::new_err(label.clone().unbind())),
+        }
+    }
+
+    fn label_of(&self, py: Python<'_>, index: common_types::InputInt) -> PyResult<PyObject> {
+        self.labels
+            .get(index as usize)
+            .map(|label| label.clone_ref(py))
+            .ok_or_else(|| PyIndexError::new_err(format!("no item at index {index}")))
+    }
+
+    /// Stream all matchups of the catalog, as lists of labels.
+    #[pyo3(signature = (match_size, ordering = "inter_first", seed = None))]
+    fn stream_matches(
+        slf: &Bound<'_, Self>,
+        match_size: common_types::BaseInt,
+        ordering: &str,
+        seed: Option<u64>,
+    ) -> PyResult<LabelledMatchupStream> {
+        let network_size = common_types::InputInt::try_from(slf.get().labels.len())
+            .map_err(|_| to_value_error(SnicError::Overflow))?;
+        Ok(LabelledMatchupStream {
+            catalog: slf.clone().unbind(),
+            matchups: stream_matches_from(slf.py(), network_size, match_size, ordering, seed)?,
+        })
+    }
+
+    /// Resume a labelled stream of the catalog from the checkpoint of its `cursor`.
+    fn resume_matches(slf: &Bound<'_, Self>, cursor: &str) -> PyResult<LabelledMatchupStream> {
+        let matchups = resume_matches_from(cursor)?;
+        if matchups.matchups_manager.cursor().network_size as usize != slf.get().labels.len() {
+            return Err(PyValueError::new_err(
+                "the checkpoint belongs to a network of another size",
+            ));
+        }
+        Ok(LabelledMatchupStream {
+            catalog: slf.clone().unbind(),
+            matchups,
+        })
+    }
+
+    /// Rank the labels by their ranked matchups, best label first in each matchup.
+    /// Returns every label in the order of `stream_rankings_from`,
+    /// with the labels never compared last, in catalog order.
+    fn stream_rankings(
+        &self,
+        py: Python<'_>,
+        ranked_matches: Vec<Vec<Bound<'_, PyAny>>>,
+    ) -> PyResult<Vec<PyObject>> {
+        let ranked_indices = ranked_matches
+            .iter()
+            .map(|matchup| matchup.iter().map(|label| self.index_of(label)).collect())
+            .collect::<PyResult<Vec<Vec<common_types::InputInt>>>>()?;
+        let (outcome, _) = rank_outcome_of(py, &ranked_indices, None)?;
+        let mut rank_scores = outcome.scores;
+        rank_scores.resize(self.labels.len(), f32::NAN);
+        Ok(network::rank::order_by_scores(&rank_scores, true)
+            .into_iter()
+            .map(|index| self.labels[index as usize].clone_ref(py))
+            .collect())
+    }
+}
+
+/// Matchup stream of a catalog, yielding labels instead of indices.
+#[pyclass(module = "snic")]
+struct LabelledMatchupStream {
+    catalog: Py<Catalog>,
+    matchups: MatchupStream,
+}
+
+#[pymethods]
+impl LabelledMatchupStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> Option<Vec<PyObject>> {
+        let labels = &self.catalog.get().labels;
+        self.matchups
+            .matchups_manager
+            .next()
+            .map(|matchup| {
+                matchup
+                    .into_iter()
+                    .map(|index| labels[index as usize].clone_ref(py))
+                    .collect()
+            })
+    }
+
+    fn __length_hint__(&self) -> usize {
+        self.matchups.__length_hint__()
+    }
+
+    fn cursor(&self) -> PyResult<String> {
+        self.matchups.cursor()
+    }
+}
+
+/// Generalized base exponential representation of a network size.
+#[pyclass(module = "snic", name = "Decomposition", frozen)]
+struct PyDecomposition {
+    decomposition: gber::Decomposition,
+}
+
+#[pymethods]
+impl PyDecomposition {
+    #[new]
+    fn new(number: common_types::InputInt, base: common_types::BaseInt) -> PyResult<Self> {
+        Ok(Self {
+            decomposition: gber::Decomposition::new(number, base).map_err(to_value_error)?,
+        })
+    }
+
+    #[getter]
+    fn base(&self) -> common_types::BaseInt {
+        self.decomposition.base
+    }
+
+    #[getter]
+    fn component_powers(&self) -> Vec<u8> {
+        self.decomposition.component_powers.clone()
+    }
+
+    #[getter]
+    fn remainder(&self) -> common_types::BaseInt {
+        self.decomposition.remainder
+    }
+
+    /// Subnetwork sizes, largest first.
+    fn components(&self) -> Vec<common_types::InputInt> {
+        self.decomposition.stream_all_components().collect()
+    }
+
+    fn to_decimal(&self) -> PyResult<common_types::InputInt> {
+        self.decomposition.to_decimal().map_err(to_value_error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Decomposition(base={}, component_powers={:?}, remainder={})",
+            self.decomposition.base, self.decomposition.component_powers, self.decomposition.remainder
+        )
+    }
+}
+
+/// Size of the matchup plan of a network.
+#[pyclass(module = "snic", frozen, get_all)]
+struct PlanStats {
+    decomposition: Py<PyDecomposition>,
+    subnetwork_sizes: Vec<common_types::InputInt>,
+    subnetwork_matchups: Vec<u64>,
+    intersubnetwork_matchups: u64,
+    total_matchups: u64,
+    total_comparisons: u64,
+    /// Histogram of the items by the number of matchups they take part in,
+    /// mapping a number of matchups to the number of items playing that many.
+    /// `MatchupStream.matchups_containing` gives the matchups of one item.
+    participation_histogram: std::collections::BTreeMap<u64, common_types::InputInt>,
+}
+
+/// Count the matchups, comparisons and participation histogram of a plan
+/// without generating it.
+#[pyfunction]
+#[pyo3(signature = (network_size, match_size))]
+fn plan_stats(
+    py: Python<'_>,
+    network_size: common_types::InputInt,
+    match_size: common_types::BaseInt,
+) -> PyResult<PlanStats> {
+    let statistics =
+        network::statistics::calculate_plan_statistics_for(network_size, match_size)
+            .map_err(to_value_error)?;
+    Ok(PlanStats {
+        decomposition: Py::new(
+            py,
+            PyDecomposition {
+                decomposition: statistics.decomposition,
+            },
+        )?,
+        subnetwork_sizes: statistics.subnetwork_sizes,
+        subnetwork_matchups: statistics.subnetwork_matchups,
+        intersubnetwork_matchups: statistics.intersubnetwork_matchups,
+        total_matchups: statistics.total_matchups,
+        total_comparisons: statistics.total_comparisons,
+        participation_histogram: statistics.participation_histogram.into_iter().collect(),
+    })
+}
+
+fn to_value_error(error: SnicError) -> PyErr {
+    PyValueError::new_err(error.to_string())
 }
 
 #[pymodule]
 fn snic(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(stream_matches_from, m)?)?;
+    m.add_function(wrap_pyfunction!(stream_shard_from, m)?)?;
+    m.add_function(wrap_pyfunction!(resume_matches_from, m)?)?;
     m.add_function(wrap_pyfunction!(stream_rankings_from, m)?)?;
+    m.add_function(wrap_pyfunction!(matches_array_from, m)?)?;
+    m.add_function(wrap_pyfunction!(rank_matches_array, m)?)?;
+    m.add_function(wrap_pyfunction!(plan_stats, m)?)?;
+    m.add_class::<MatchupStream>()?;
+    m.add_class::<MatchupShardStream>()?;
+    m.add_class::<PyDecomposition>()?;
+    m.add_class::<PlanStats>()?;
+    m.add_class::<Ranking>()?;
+    m.add_class::<Catalog>()?;
+    m.add_class::<LabelledMatchupStream>()?;
     Ok(())
 }
 